@@ -1,28 +1,261 @@
+use std::iter::Peekable;
+use std::str::Chars;
+
 use proc_macro::TokenStream;
 
 use proc_macro2::TokenStream as TokenStream2;
 use quote::ToTokens;
-use syn::{Attribute, Error, Path};
+use syn::{Attribute, Error, Lit, LitInt, MetaNameValue, Path, Token, Type, parenthesized};
 use syn::{ExprAssign, LitStr};
-use syn::parse::Parser;
+use syn::parse::{Parse, ParseStream, Parser};
 use syn::parse_macro_input::parse;
 use syn::punctuated::Punctuated;
 use syn::token::Comma;
 
+#[derive(Default)]
 pub(crate) struct ProvidesAttr {
     pub profiles: Vec<Path>,
+    pub order: Option<i32>,
+    pub name: Option<String>,
+    pub profile_expr: Option<ProfileExpr>,
+    pub when_props: Vec<(String, Option<String>)>,
+    pub when_missing: Vec<Type>,
+}
+
+impl ProvidesAttr {
+    pub fn is_conditional(&self) -> bool {
+        self.profile_expr.is_some() || !self.when_props.is_empty() || !self.when_missing.is_empty()
+    }
+}
+
+enum ProvidesArg {
+    Profile(Path),
+    ProfileExpr(ProfileExpr),
+    Order(i32),
+    Name(String),
+    WhenProp(String, Option<String>),
+    WhenMissing(Box<Type>),
+}
+
+impl Parse for ProvidesArg {
+    fn parse(input: ParseStream) -> Result<Self, Error> {
+        let path = input.parse::<Path>()?;
+        if path.is_ident("when_prop") && input.peek(syn::token::Paren) {
+            let content;
+            parenthesized!(content in input);
+            let prop = content.parse::<LitStr>()?.value();
+            content.parse::<Token![=]>()?;
+            let value = content.parse::<LitStr>()?.value();
+            return Ok(ProvidesArg::WhenProp(prop, Some(value)));
+        }
+        if !input.peek(Token![=]) {
+            return Ok(ProvidesArg::Profile(path));
+        }
+
+        input.parse::<Token![=]>()?;
+        if path.is_ident("order") {
+            Ok(ProvidesArg::Order(input.parse::<LitInt>()?.base10_parse()?))
+        } else if path.is_ident("name") {
+            Ok(ProvidesArg::Name(input.parse::<LitStr>()?.value()))
+        } else if path.is_ident("profile") {
+            let expression = input.parse::<LitStr>()?;
+            ProfileExpr::parse(&expression.value())
+                .map(ProvidesArg::ProfileExpr)
+                .map_err(|cause| Error::new_spanned(
+                    &expression,
+                    format!("Invalid profile expression \"{}\": {}", expression.value(), cause),
+                ))
+        } else if path.is_ident("when_prop") {
+            Ok(ProvidesArg::WhenProp(input.parse::<LitStr>()?.value(), None))
+        } else if path.is_ident("when_missing") {
+            Ok(ProvidesArg::WhenMissing(Box::new(input.parse::<Type>()?)))
+        } else {
+            Err(Error::new_spanned(path, "Unsupported #[provides] argument"))
+        }
+    }
 }
 
 pub(crate) fn parse_provides_attr(attr: TokenStream) -> Result<ProvidesAttr, Error> {
-    let profiles_syn = <Punctuated<Path, Comma>>::parse_terminated.parse(attr)?;
+    let args = <Punctuated<ProvidesArg, Comma>>::parse_terminated.parse(attr)?;
+
+    let mut provides = ProvidesAttr::default();
+    for arg in args {
+        match arg {
+            ProvidesArg::Profile(profile) => provides.profiles.push(profile),
+            ProvidesArg::Order(order) => provides.order = Some(order),
+            ProvidesArg::Name(name) => provides.name = Some(name),
+            ProvidesArg::ProfileExpr(expr) => provides.profile_expr = Some(expr),
+            ProvidesArg::WhenProp(prop, value) => provides.when_props.push((prop, value)),
+            ProvidesArg::WhenMissing(type_) => provides.when_missing.push(*type_),
+        }
+    }
 
-    let profiles: Vec<Path> = profiles_syn
-        .iter().cloned()
-        .collect();
+    Ok(provides)
+}
 
-    Ok(ProvidesAttr { profiles })
+/// Profile expression like `dev & !mock-db`, it's generated as `waiter_di::ProfileExpr`
+pub(crate) enum ProfileExpr {
+    Profile(String),
+    Not(Box<ProfileExpr>),
+    And(Box<ProfileExpr>, Box<ProfileExpr>),
+    Or(Box<ProfileExpr>, Box<ProfileExpr>),
 }
 
+impl ProfileExpr {
+    fn parse(expression: &str) -> Result<ProfileExpr, String> {
+        let mut chars = expression.chars().peekable();
+        let expr = Self::parse_or(&mut chars)?;
+
+        skip_whitespaces(&mut chars);
+        match chars.next() {
+            Some(unexpected) => Err(format!("Unexpected '{}'", unexpected)),
+            None => Ok(expr)
+        }
+    }
+
+    fn parse_or(chars: &mut Peekable<Chars>) -> Result<ProfileExpr, String> {
+        let mut expr = Self::parse_and(chars)?;
+        while next_is(chars, '|') {
+            expr = ProfileExpr::Or(Box::new(expr), Box::new(Self::parse_and(chars)?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(chars: &mut Peekable<Chars>) -> Result<ProfileExpr, String> {
+        let mut expr = Self::parse_unary(chars)?;
+        while next_is(chars, '&') {
+            expr = ProfileExpr::And(Box::new(expr), Box::new(Self::parse_unary(chars)?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_unary(chars: &mut Peekable<Chars>) -> Result<ProfileExpr, String> {
+        if next_is(chars, '!') {
+            return Ok(ProfileExpr::Not(Box::new(Self::parse_unary(chars)?)));
+        }
+
+        if next_is(chars, '(') {
+            let expr = Self::parse_or(chars)?;
+            return if next_is(chars, ')') {
+                Ok(expr)
+            } else {
+                Err("Expected ')'".to_string())
+            };
+        }
+
+        let mut profile = String::new();
+        while let Some(c) = chars.peek() {
+            if c.is_alphanumeric() || *c == '_' || *c == '-' || *c == '.' {
+                profile.push(*c);
+                chars.next();
+            } else {
+                break;
+            }
+        }
+
+        if profile.is_empty() {
+            Err("Expected profile name".to_string())
+        } else {
+            Ok(ProfileExpr::Profile(profile))
+        }
+    }
+
+    pub fn to_tokens(&self) -> TokenStream2 {
+        match self {
+            ProfileExpr::Profile(profile) => quote::quote! { waiter_di::ProfileExpr::Profile(#profile) },
+            ProfileExpr::Not(expr) => {
+                let expr = expr.to_tokens();
+                quote::quote! { waiter_di::ProfileExpr::Not(&#expr) }
+            }
+            ProfileExpr::And(left, right) => {
+                let (left, right) = (left.to_tokens(), right.to_tokens());
+                quote::quote! { waiter_di::ProfileExpr::And(&#left, &#right) }
+            }
+            ProfileExpr::Or(left, right) => {
+                let (left, right) = (left.to_tokens(), right.to_tokens());
+                quote::quote! { waiter_di::ProfileExpr::Or(&#left, &#right) }
+            }
+        }
+    }
+}
+
+fn skip_whitespaces(chars: &mut Peekable<Chars>) {
+    while chars.peek().map(|c| c.is_whitespace()).unwrap_or(false) {
+        chars.next();
+    }
+}
+
+fn next_is(chars: &mut Peekable<Chars>, expected: char) -> bool {
+    skip_whitespaces(chars);
+    if chars.peek() == Some(&expected) {
+        chars.next();
+        skip_whitespaces(chars);
+        true
+    } else {
+        false
+    }
+}
+
+#[derive(Clone, Default)]
+pub(crate) enum ComponentScope {
+    #[default]
+    Singleton,
+    Prototype,
+    /// Type implementing `waiter_di::Scope`, built-in `thread` and `request` scopes are
+    /// `waiter_di::ThreadScope` and `waiter_di::RequestScope`
+    Custom(Path),
+}
+
+#[derive(Default)]
+pub(crate) struct ComponentAttr {
+    pub scope: ComponentScope,
+}
+
+pub(crate) fn parse_component_attr(attr: TokenStream) -> Result<ComponentAttr, Error> {
+    let args = <Punctuated<MetaNameValue, Comma>>::parse_terminated.parse(attr)?;
+
+    let mut component = ComponentAttr::default();
+    for arg in args {
+        if !arg.path.is_ident("scope") {
+            return Err(Error::new_spanned(arg.path, "Unsupported #[component] argument"));
+        }
+        let scope = match &arg.lit {
+            Lit::Str(scope) => scope,
+            other => return Err(Error::new_spanned(other, "Scope must be a string"))
+        };
+        component.scope = match scope.value().as_str() {
+            "singleton" => ComponentScope::Singleton,
+            "prototype" => ComponentScope::Prototype,
+            "thread" => ComponentScope::Custom(syn::parse_quote! { waiter_di::ThreadScope }),
+            "request" => ComponentScope::Custom(syn::parse_quote! { waiter_di::RequestScope }),
+            _ => ComponentScope::Custom(scope.parse::<Path>()?)
+        };
+    }
+
+    Ok(component)
+}
+
+#[derive(Default)]
+pub(crate) struct ConfigPropertiesAttr {
+    pub prefix: String,
+}
+
+pub(crate) fn parse_config_properties_attr(attr: TokenStream) -> Result<ConfigPropertiesAttr, Error> {
+    let args = <Punctuated<MetaNameValue, Comma>>::parse_terminated.parse(attr)?;
+
+    let mut config_properties = ConfigPropertiesAttr::default();
+    for arg in args {
+        if !arg.path.is_ident("prefix") {
+            return Err(Error::new_spanned(arg.path, "Unsupported #[config_properties] argument"));
+        }
+        config_properties.prefix = match &arg.lit {
+            Lit::Str(prefix) => prefix.value(),
+            other => return Err(Error::new_spanned(other, "Prefix must be a string"))
+        };
+    }
+
+    Ok(config_properties)
+}
 
 #[derive(Clone)]
 pub(crate) struct PropAttr {
@@ -50,4 +283,42 @@ pub(crate) fn parse_prop_attr(attr: &Attribute) -> Result<PropAttr, Error> {
                 })
             })
     }
+}
+
+pub(crate) fn parse_inject_attr(attr: &Attribute) -> Result<String, Error> {
+    Ok(attr.parse_args::<LitStr>()?.value())
+}
+
+/// Names can't be used as const generic arguments, so the name is replaced by its FNV-1a hash
+pub(crate) fn qualifier_id(name: &str) -> u64 {
+    name.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::attr_parser::ProfileExpr;
+
+    #[test]
+    fn parse_expressions() {
+        let expr = ProfileExpr::parse("prod & !(eu-region | us-region)").unwrap();
+        assert_eq!(
+            "waiter_di :: ProfileExpr :: And (& waiter_di :: ProfileExpr :: Profile (\"prod\") , \
+             & waiter_di :: ProfileExpr :: Not (& waiter_di :: ProfileExpr :: Or \
+             (& waiter_di :: ProfileExpr :: Profile (\"eu-region\") , \
+             & waiter_di :: ProfileExpr :: Profile (\"us-region\"))))",
+            expr.to_tokens().to_string()
+        );
+        assert!(ProfileExpr::parse("a | b & c").is_ok());
+        assert!(ProfileExpr::parse("!!a").is_ok());
+    }
+
+    #[test]
+    fn reject_malformed_expressions() {
+        assert!(ProfileExpr::parse("dev &").is_err());
+        assert!(ProfileExpr::parse("(dev").is_err());
+        assert!(ProfileExpr::parse("dev prod").is_err());
+    }
 }
\ No newline at end of file
@@ -1,8 +1,10 @@
-use proc_macro2::TokenStream as TokenStream2;
+use proc_macro2::{Span, TokenStream as TokenStream2};
 use quote::ToTokens;
-use syn::{Error, Ident, PathArguments};
+use syn::{Error, GenericArgument, Ident, PathArguments, Type};
 use syn::spanned::Spanned;
 
+use crate::attr_parser::qualifier_id;
+use crate::component::{dependency_target, generate_inject_code};
 use crate::component::type_to_inject::TypeToInject;
 
 pub(crate) trait Injector {
@@ -11,6 +13,27 @@ pub(crate) trait Injector {
         to_inject: &TypeToInject,
         container: &Ident,
     ) -> Option<TokenStream2>;
+
+    /// Code for `AsyncProvider`, dependencies are resolved synchronously by default
+    fn generate_inject_code_async(
+        &self,
+        to_inject: &TypeToInject,
+        container: &Ident,
+    ) -> Option<TokenStream2> {
+        self.generate_inject_code(to_inject, container)
+    }
+
+    /// `waiter_di::DependencyKind` variant used in the dependency graph
+    fn dependency_kind(&self) -> TokenStream2;
+
+    /// Component or interface the dependency refers to, `None` for properties
+    fn target_type(&self, _to_inject: &TypeToInject) -> Option<TokenStream2> {
+        None
+    }
+
+    fn property_name(&self, _to_inject: &TypeToInject) -> Option<String> {
+        None
+    }
 }
 
 pub(crate) struct WrcInjector;
@@ -21,6 +44,40 @@ impl Injector for WrcInjector {
         to_inject: &TypeToInject,
         container: &Ident,
     ) -> Option<TokenStream2> {
+        self.target_type(to_inject).map(|ref_type| match &to_inject.qualifier {
+            Some(name) => {
+                let id = qualifier_id(name);
+                quote::quote! {
+                    waiter_di::NamedProvider::<#ref_type, #id>::try_get(#container)?
+                }
+            }
+            None => quote::quote! {{
+                use waiter_di::{__WaiterRuntimeResolve, __WaiterStaticResolve};
+                (&waiter_di::__WaiterResolver::<#ref_type>::new()).__waiter_try_get(#container)?
+            }}
+        })
+    }
+
+    fn generate_inject_code_async(
+        &self,
+        to_inject: &TypeToInject,
+        container: &Ident,
+    ) -> Option<TokenStream2> {
+        if to_inject.qualifier.is_some() {
+            return self.generate_inject_code(to_inject, container);
+        }
+        self.target_type(to_inject).map(|ref_type| quote::quote! {{
+            use waiter_di::{__WaiterAsyncFallbackResolve, __WaiterAsyncRuntimeResolve,
+                            __WaiterAsyncStaticResolve};
+            (&&waiter_di::__WaiterResolver::<#ref_type>::new()).__waiter_try_get_async(#container).await?
+        }})
+    }
+
+    fn dependency_kind(&self) -> TokenStream2 {
+        quote::quote! { waiter_di::DependencyKind::Wrc }
+    }
+
+    fn target_type(&self, to_inject: &TypeToInject) -> Option<TokenStream2> {
         #[cfg(feature = "async")]
         const RC_FULL_TYPE: &str = "std :: sync :: Arc <";
         #[cfg(not(feature = "async"))]
@@ -31,7 +88,7 @@ impl Injector for WrcInjector {
         #[cfg(not(feature = "async"))]
         const RC_SHORT_TYPE: &str = "Rc <";
 
-        let referenced_type_opt = if to_inject.type_name.starts_with("waiter_di :: Wrc <")
+        if to_inject.type_name.starts_with("waiter_di :: Wrc <")
             || to_inject.type_name.starts_with(RC_FULL_TYPE) {
             Some(get_type_arg(&to_inject.type_path.segments[2].arguments))
         } else if to_inject.type_name.starts_with("Wrc <") ||
@@ -39,11 +96,7 @@ impl Injector for WrcInjector {
             Some(get_type_arg(&to_inject.type_path.segments[0].arguments))
         } else {
             None
-        };
-
-        referenced_type_opt.map(|ref_type| quote::quote! {
-            waiter_di::Provider::<#ref_type>::get(#container)
-        })
+        }
     }
 }
 
@@ -58,13 +111,92 @@ impl Injector for BoxInjector {
     ) -> Option<TokenStream2> {
         if to_inject.type_name.starts_with("Box <") {
             let referenced_type = get_type_arg(&to_inject.type_path.segments[0].arguments);
-            return Some(quote::quote! {
-                Box::new(waiter_di::Provider::<#referenced_type>::create(#container))
+            return Some(match &to_inject.qualifier {
+                Some(name) => {
+                    let id = qualifier_id(name);
+                    quote::quote! {
+                        Box::new(waiter_di::NamedProvider::<#referenced_type, #id>::try_create(#container)?)
+                    }
+                }
+                None => quote::quote! {{
+                    use waiter_di::{__WaiterRuntimeResolve, __WaiterStaticResolve};
+                    (&waiter_di::__WaiterResolver::<#referenced_type>::new()).__waiter_try_create(#container)?
+                }}
             });
         }
 
         None
     }
+
+    fn generate_inject_code_async(
+        &self,
+        to_inject: &TypeToInject,
+        container: &Ident,
+    ) -> Option<TokenStream2> {
+        if to_inject.qualifier.is_some() {
+            return self.generate_inject_code(to_inject, container);
+        }
+        self.target_type(to_inject).map(|referenced_type| quote::quote! {{
+            use waiter_di::{__WaiterAsyncFallbackResolve, __WaiterAsyncRuntimeResolve,
+                            __WaiterAsyncStaticResolve};
+            (&&waiter_di::__WaiterResolver::<#referenced_type>::new()).__waiter_try_create_async(#container).await?
+        }})
+    }
+
+    fn dependency_kind(&self) -> TokenStream2 {
+        quote::quote! { waiter_di::DependencyKind::Box }
+    }
+
+    fn target_type(&self, to_inject: &TypeToInject) -> Option<TokenStream2> {
+        if to_inject.type_name.starts_with("Box <") {
+            Some(get_type_arg(&to_inject.type_path.segments[0].arguments))
+        } else {
+            None
+        }
+    }
+}
+
+
+pub(crate) struct VecInjector;
+
+impl Injector for VecInjector {
+    fn generate_inject_code(
+        &self,
+        to_inject: &TypeToInject,
+        container: &Ident,
+    ) -> Option<TokenStream2> {
+        if !to_inject.type_name.starts_with("Vec <") {
+            return None;
+        }
+
+        let item_type = get_first_type_arg(&to_inject.type_path.segments[0].arguments)?;
+        let item_path = if let Type::Path(item_path) = item_type {
+            &item_path.path
+        } else {
+            return None;
+        };
+
+        let pointer = item_path.segments.last()?;
+        let interface = get_type_arg(&pointer.arguments);
+        match pointer.ident.to_string().as_str() {
+            "Wrc" | "Rc" | "Arc" => Some(quote::quote! {
+                #container.try_get_all::<#interface>()?
+            }),
+            "Box" => Some(quote::quote! {
+                #container.try_create_all::<#interface>()?
+            }),
+            _ => None
+        }
+    }
+
+    fn dependency_kind(&self) -> TokenStream2 {
+        quote::quote! { waiter_di::DependencyKind::Vec }
+    }
+
+    fn target_type(&self, to_inject: &TypeToInject) -> Option<TokenStream2> {
+        let item_type = get_first_type_arg(&to_inject.type_path.segments[0].arguments)?;
+        get_pointee(item_type).map(|pointee| pointee.to_token_stream())
+    }
 }
 
 
@@ -88,6 +220,103 @@ impl Injector for DeferredInjector {
             waiter_di::Deferred::<#ref_type>::new()
         })
     }
+
+    fn dependency_kind(&self) -> TokenStream2 {
+        quote::quote! { waiter_di::DependencyKind::Deferred }
+    }
+
+    fn target_type(&self, to_inject: &TypeToInject) -> Option<TokenStream2> {
+        let deferred = to_inject.type_path.segments.last()?;
+        let deferred_type = get_first_type_arg(&deferred.arguments)?;
+        Some(get_pointee(deferred_type).unwrap_or(deferred_type).to_token_stream())
+    }
+}
+
+
+pub(crate) struct WeakRefInjector;
+
+impl Injector for WeakRefInjector {
+    fn generate_inject_code(
+        &self,
+        to_inject: &TypeToInject,
+        _container: &Ident,
+    ) -> Option<TokenStream2> {
+        self.target_type(to_inject).map(|ref_type| quote::quote! {
+            waiter_di::WeakRef::<#ref_type>::new()
+        })
+    }
+
+    fn dependency_kind(&self) -> TokenStream2 {
+        quote::quote! { waiter_di::DependencyKind::Weak }
+    }
+
+    fn target_type(&self, to_inject: &TypeToInject) -> Option<TokenStream2> {
+        if to_inject.type_name.starts_with("waiter_di :: WeakRef <")
+            || to_inject.type_name.starts_with("WeakRef <") {
+            let weak_ref = to_inject.type_path.segments.last()?;
+            Some(get_type_arg(&weak_ref.arguments))
+        } else {
+            None
+        }
+    }
+}
+
+
+pub(crate) struct LazyInjector;
+
+impl Injector for LazyInjector {
+    fn generate_inject_code(
+        &self,
+        to_inject: &TypeToInject,
+        container: &Ident,
+    ) -> Option<TokenStream2> {
+        let lazy_type = get_wrapped_type(to_inject, "Lazy")?;
+        let inject_code = generate_inject_code(&lazy_type, &Ident::new("container", Span::call_site()));
+
+        Some(quote::quote! {
+            waiter_di::Lazy::new(#container.__waiter_detach(), |container| {
+                let value = #inject_code;
+                Ok(value)
+            })
+        })
+    }
+
+    fn dependency_kind(&self) -> TokenStream2 {
+        quote::quote! { waiter_di::DependencyKind::Lazy }
+    }
+
+    fn target_type(&self, to_inject: &TypeToInject) -> Option<TokenStream2> {
+        dependency_target(&get_wrapped_type(to_inject, "Lazy")?)
+    }
+}
+
+
+pub(crate) struct FactoryInjector;
+
+impl Injector for FactoryInjector {
+    fn generate_inject_code(
+        &self,
+        to_inject: &TypeToInject,
+        container: &Ident,
+    ) -> Option<TokenStream2> {
+        let created_type = get_wrapped_type(to_inject, "Factory")?;
+        let inject_code = generate_inject_code(&created_type, &Ident::new("container", Span::call_site()));
+
+        Some(quote::quote! {
+            waiter_di::Factory::new(#container.__waiter_detach(), |container| {
+                let value = #inject_code;
+                Ok(value)
+            })
+        })
+    }
+
+    fn dependency_kind(&self) -> TokenStream2 {
+        quote::quote! { waiter_di::DependencyKind::Factory }
+    }
+
+    fn target_type(&self, to_inject: &TypeToInject) -> Option<TokenStream2> {
+        dependency_target(&get_wrapped_type(to_inject, "Factory")?)
+    }
 }
 
 
@@ -106,6 +335,51 @@ impl Injector for ConfigInjector {
 
         None
     }
+
+    fn dependency_kind(&self) -> TokenStream2 {
+        quote::quote! { waiter_di::DependencyKind::Config }
+    }
+}
+
+
+pub(crate) struct ReloadableInjector;
+
+impl Injector for ReloadableInjector {
+    fn generate_inject_code(
+        &self,
+        to_inject: &TypeToInject,
+        container: &Ident,
+    ) -> Option<TokenStream2> {
+        let value_type = self.value_type(to_inject)?;
+        let prop_name = self.property_name(to_inject)?;
+
+        Some(quote::quote! {
+            waiter_di::Reloadable::<#value_type>::new(
+                #container.__waiter_live_config(),
+                #prop_name,
+            )?
+        })
+    }
+
+    fn dependency_kind(&self) -> TokenStream2 {
+        quote::quote! { waiter_di::DependencyKind::Prop }
+    }
+
+    fn property_name(&self, to_inject: &TypeToInject) -> Option<String> {
+        self.value_type(to_inject)?;
+        PropInjector.property_name(to_inject)
+    }
+}
+
+impl ReloadableInjector {
+    fn value_type(&self, to_inject: &TypeToInject) -> Option<TokenStream2> {
+        if to_inject.type_name.starts_with("waiter_di :: Reloadable <")
+            || to_inject.type_name.starts_with("Reloadable <") {
+            Some(get_type_arg(&to_inject.type_path.segments.last()?.arguments))
+        } else {
+            None
+        }
+    }
 }
 
 
@@ -145,7 +419,9 @@ impl Injector for PropInjector {
                 )
             } else {
                 (to_inject.type_path.to_token_stream(),
-                 quote::quote! { .expect(format!("Property \"{}\" not found", #prop_name).as_str()) }
+                 quote::quote! {
+                     .ok_or_else(|| waiter_di::ResolveError::missing_property(#prop_name))?
+                 }
                 )
             };
             let type_name = type_path.to_string();
@@ -167,9 +443,13 @@ impl Injector for PropInjector {
                         );
 
                         quote::quote! {
-                            #container.config.#extract_method(#prop_name)
+                            waiter_di::ResolveError::check_property(
+                                #container.config.#extract_method(#prop_name),
+                                #prop_name,
+                                #type_name,
+                            )?
                                 .map(|value| #convert_code)
-                                .ok()
+                                .transpose()?
                                 #default_value_code
                                 #opt_extractor
                         }
@@ -179,18 +459,53 @@ impl Injector for PropInjector {
 
         base_types_extracted
             .or_else(|| {
-                if to_inject.prop_attr.is_some() {
+                let prop_attr = to_inject.prop_attr.as_ref()?;
+                if let Some(prop_name) = &prop_attr.name {
+                    // Struct types are deserialized from their section, e.g. `#[prop("database")]`
+                    let default_value_code = prop_attr.default_value.as_ref()
+                        .map(|default_value| quote::quote! { .or_else(|| Some(#default_value)) })
+                        .unwrap_or_default();
+                    let (type_path, opt_extractor) = if to_inject.type_name.starts_with("Option <") {
+                        (get_type_arg(&to_inject.type_path.segments[0].arguments), quote::quote! {})
+                    } else {
+                        (to_inject.type_path.to_token_stream(), quote::quote! {
+                            .ok_or_else(|| waiter_di::ResolveError::missing_property(#prop_name))?
+                        })
+                    };
+                    let type_name = type_path.to_string();
+                    Some(quote::quote! {
+                        waiter_di::ResolveError::check_property(
+                            #container.config.get::<#type_path>(#prop_name),
+                            #prop_name,
+                            #type_name,
+                        )?
+                            #default_value_code
+                            #opt_extractor
+                    })
+                } else {
                     let type_name = to_inject.type_name.clone();
                     let type_path = to_inject.type_path.clone();
                     Some(quote::quote! {
                         #container.config.clone().try_deserialize::<#type_path>()
-                            .expect(format!("Can't parse config as '{}'", #type_name).as_str())
+                            .map_err(|error| waiter_di::ResolveError::invalid_property(
+                                "",
+                                #type_name,
+                                error,
+                            ))?
                     })
-                } else {
-                    None
                 }
             })
     }
+
+    fn dependency_kind(&self) -> TokenStream2 {
+        quote::quote! { waiter_di::DependencyKind::Prop }
+    }
+
+    fn property_name(&self, to_inject: &TypeToInject) -> Option<String> {
+        to_inject.prop_attr.as_ref()
+            .and_then(|prop_attr| prop_attr.name.clone())
+            .or_else(|| to_inject.arg_name.as_ref().map(|name| name.to_string()))
+    }
 }
 
 trait PropExtractor {
@@ -202,7 +517,7 @@ trait PropExtractor {
         _prop_name: String,
         extract_code: TokenStream2,
     ) -> TokenStream2 {
-        extract_code
+        quote::quote! { Ok::<_, waiter_di::ResolveError>(#extract_code) }
     }
 }
 
@@ -223,7 +538,7 @@ impl PropExtractor for SafeCastPropExtractor {
         _prop_name: String,
         value: TokenStream2,
     ) -> TokenStream2 {
-        quote::quote! { #type_path::from(#value) }
+        quote::quote! { Ok::<_, waiter_di::ResolveError>(#type_path::from(#value)) }
     }
 }
 
@@ -247,7 +562,11 @@ impl PropExtractor for UnsafeCastPropExtractor {
     ) -> TokenStream2 {
         quote::quote! {
             <#type_path as std::convert::TryFrom<i64>>::try_from(#value)
-                .expect(format!("Can't parse prop '{}' as '{}'", #prop_name, #type_name).as_str())
+                .map_err(|error| waiter_di::ResolveError::invalid_property(
+                    #prop_name,
+                    #type_name,
+                    error,
+                ))
         }
     }
 }
@@ -273,9 +592,42 @@ impl PropExtractor for AsCastPropExtractor {
         value: TokenStream2,
     ) -> TokenStream2 {
         quote::quote! {
-            #value as #type_path
+            Ok::<_, waiter_di::ResolveError>(#value as #type_path)
+        }
+    }
+}
+
+/// Type under `Lazy` or `Factory`, it's injected by the same rules as a field of this type
+fn get_wrapped_type(to_inject: &TypeToInject, wrapper: &str) -> Option<TypeToInject> {
+    if !to_inject.type_name.starts_with(&format!("{} <", wrapper))
+        && !to_inject.type_name.starts_with(&format!("waiter_di :: {} <", wrapper)) {
+        return None;
+    }
+
+    let wrapped_type = get_first_type_arg(&to_inject.type_path.segments.last()?.arguments)?;
+    let mut wrapped = TypeToInject::from_type(wrapped_type).ok()?;
+    wrapped.qualifier = to_inject.qualifier.clone();
+    Some(wrapped)
+}
+
+fn get_first_type_arg(arguments: &PathArguments) -> Option<&Type> {
+    if let PathArguments::AngleBracketed(ab) = arguments {
+        if let Some(GenericArgument::Type(type_)) = ab.args.first() {
+            return Some(type_);
+        }
+    }
+    None
+}
+
+/// Type under `Wrc`, `Rc`, `Arc` or `Box` pointer
+fn get_pointee(type_: &Type) -> Option<&Type> {
+    if let Type::Path(type_path) = type_ {
+        let pointer = type_path.path.segments.last()?;
+        if ["Wrc", "Rc", "Arc", "Box"].contains(&pointer.ident.to_string().as_str()) {
+            return get_first_type_arg(&pointer.arguments);
         }
     }
+    None
 }
 
 fn get_type_arg(arguments: &PathArguments) -> TokenStream2 {
@@ -2,34 +2,33 @@ use proc_macro::TokenStream;
 
 use proc_macro2::{Span, TokenStream as TokenStream2};
 use quote::ToTokens;
-use syn::{Error, Expr, Field, Fields, GenericArgument, Ident, ImplItem, ItemFn, ItemImpl, ItemStruct, PathArguments, Type};
+use syn::{Error, Field, Fields, GenericArgument, Ident, ImplItem, ItemFn, ItemImpl, ItemStruct, PathArguments, Type};
 use syn::spanned::Spanned;
 
-use crate::attr_parser::parse_provides_attr;
-use crate::component::injector::{BoxInjector, ConfigInjector, DeferredInjector, Injector,
-                                 PropInjector, WrcInjector};
+use crate::attr_parser::{parse_provides_attr, ComponentScope};
+use crate::component::injector::{BoxInjector, ConfigInjector, DeferredInjector, FactoryInjector,
+                                 Injector, LazyInjector, PropInjector, ReloadableInjector, VecInjector,
+                                 WeakRefInjector, WrcInjector};
 use crate::component::type_to_inject::TypeToInject;
 use crate::provider::generate_component_provider_impl_fn;
 
 pub(crate) mod injector;
 pub(crate) mod type_to_inject;
 
-pub(crate) fn generate_component_for_impl(comp_impl: ItemImpl) -> Result<TokenStream, Error> {
+pub(crate) fn generate_component_for_impl(
+    comp_impl: ItemImpl,
+    scope: ComponentScope,
+) -> Result<TokenStream, Error> {
     for item in &comp_impl.items {
         if let ImplItem::Method(method) = item {
             let provides_attr = method.attrs.iter()
                 .find(|attr| attr.path.to_token_stream().to_string() == *"provides");
 
-            if provides_attr.is_some() {
-                let provides_attr = provides_attr.unwrap();
+            if let Some(provides_attr) = provides_attr {
                 let provides = if provides_attr.tokens.is_empty() {
                     parse_provides_attr(TokenStream::new())?
                 } else {
-                    parse_provides_attr(
-                        provides_attr.parse_args::<Expr>()?
-                            .to_token_stream()
-                            .into()
-                    )?
+                    parse_provides_attr(provides_attr.parse_args::<TokenStream2>()?.into())?
                 };
 
                 let mut fn_tokens = method.sig.to_token_stream();
@@ -40,6 +39,7 @@ pub(crate) fn generate_component_for_impl(comp_impl: ItemImpl) -> Result<TokenSt
                     provides,
                     item_fn,
                     comp_impl.self_ty.to_token_stream(),
+                    scope,
                 );
             }
         }
@@ -51,11 +51,25 @@ pub(crate) fn generate_component_for_struct(component: ItemStruct) -> Result<Tok
     let comp_name = &component.ident;
     let comp_generics = &component.generics;
 
-    let dependencies_code = generate_dependencies_create_code(
-        component.fields.iter()
-            .map(TypeToInject::from_field)
-            .collect::<Result<Vec<_>, _>>()?
-    );
+    let self_dependency_error = check_self_dependency(&component)
+        .err()
+        .map(|error| error.to_compile_error());
+    let weak_ref_checks = if comp_generics.params.is_empty() {
+        generate_weak_ref_checks(&component.fields)
+    } else {
+        quote::quote! {}
+    };
+
+    let dependencies = component.fields.iter()
+        .map(TypeToInject::from_field)
+        .collect::<Result<Vec<_>, _>>()?;
+    let component_info = if comp_generics.params.is_empty() {
+        generate_component_info(&comp_name.to_token_stream(), &dependencies)
+    } else {
+        quote::quote! {}
+    };
+    let dependencies_async = dependencies.clone();
+    let dependencies_code = generate_dependencies_create_code(dependencies);
     let deferred_dependencies_code = generate_deferred_dependencies_code(
         component.fields.iter().collect()
     )?;
@@ -70,28 +84,73 @@ pub(crate) fn generate_component_for_struct(component: ItemStruct) -> Result<Tok
             generate_inject_deferred(fields.unnamed.iter().collect(), true)
         ),
         Fields::Unit => (
-            generate_inject_dependencies_tuple(0),
+            quote::quote! {},
             generate_inject_deferred(vec!(), true)
         ),
     };
 
+    let create_async_code = if comp_generics.params.is_empty() {
+        generate_create_async(comp_name, &dependencies_async, &factory_code)
+    } else {
+        quote::quote! {}
+    };
 
     let result = quote::quote! {
         impl #comp_generics waiter_di::Component for #comp_name #comp_generics {
-            fn __waiter_create<P>(container: &mut waiter_di::Container<P>) -> Self {
+            fn __waiter_create<P>(
+                container: &mut waiter_di::Container<P>
+            ) -> Result<Self, waiter_di::ResolveError> {
                 #dependencies_code
-                return #comp_name #factory_code;
+                return Ok(#comp_name #factory_code);
             }
-            fn __waiter_inject_deferred<P>(container: &mut waiter_di::Container<P>, component: &Self) {
+            fn __waiter_inject_deferred<P>(
+                container: &mut waiter_di::Container<P>,
+                component: &Self,
+            ) -> Result<(), waiter_di::ResolveError> {
                 #deferred_dependencies_code
                 #deferred_inject_code
+                return Ok(());
             }
         }
+        #create_async_code
+        #component_info
+        #self_dependency_error
+        #weak_ref_checks
     };
 
     Ok(result.into())
 }
 
+/// Cycles between components are detected when they are resolved, since macros don't see other
+/// components, but a component that depends on itself is rejected at compile time
+fn check_self_dependency(component: &ItemStruct) -> Result<(), Error> {
+    let comp_name = component.ident.to_string();
+    let self_types: Vec<String> = ["Self".to_string(), comp_name.clone()].iter()
+        .flat_map(|name| vec!(
+            name.clone(),
+            format!("Wrc < {} >", name),
+            format!("Rc < {} >", name),
+            format!("Arc < {} >", name),
+            format!("Box < {} >", name),
+        ))
+        .collect();
+
+    for field in &component.fields {
+        let field_type = field.ty.to_token_stream().to_string();
+        let field_type = field_type.rsplit(":: ").next().unwrap_or(&field_type);
+        if self_types.iter().any(|self_type| self_type == field_type) {
+            return Err(Error::new(
+                field.ty.span(),
+                format!(
+                    "Dependency cycle {0} -> {0}, inject it as Deferred to break the cycle",
+                    comp_name
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
 pub(crate) fn generate_inject_dependencies_tuple(dep_number: usize) -> TokenStream2 {
     let dependencies: Vec<Ident> = (0..dep_number)
         .map(|i| Ident::new(format!("dep_{}", i).as_str(), Span::call_site()))
@@ -116,25 +175,76 @@ fn generate_inject_dependencies_named(fields: Vec<&Field>) -> TokenStream2 {
     }
 }
 
+/// `Deferred` and `WeakRef` fields are injected after the component is created
+fn is_deferred(field: &Field) -> bool {
+    if let Type::Path(path_type) = &field.ty {
+        let ptr_type = path_type.path.to_token_stream().to_string();
+
+        return ptr_type.starts_with("waiter :: Deferred <") || ptr_type.starts_with("Deferred <")
+            || is_weak_ref(&field.ty);
+    }
+    false
+}
+
+pub(crate) fn has_deferred_fields(fields: &Fields) -> bool {
+    fields.iter().any(is_deferred)
+}
+
+/// Prototypes aren't kept by the container, so `WeakRef` to them is rejected at compile time.
+/// Interfaces are checked only by the component type that implements them
+fn generate_weak_ref_checks(fields: &Fields) -> TokenStream2 {
+    let checks = fields.iter()
+        .filter(|f| is_weak_ref(&f.ty))
+        .filter_map(|f| match &f.ty {
+            Type::Path(path_type) => match &path_type.path.segments.last()?.arguments {
+                PathArguments::AngleBracketed(args) => match args.args.first()? {
+                    GenericArgument::Type(target) => Some(target.clone()),
+                    _ => None
+                },
+                _ => None
+            },
+            _ => None
+        })
+        .map(|target| {
+            let check = quote::quote_spanned! {target.span()=>
+                assert!(
+                    !waiter_di::__WaiterWeakRefTarget::<#target>::PROTOTYPE,
+                    concat!(
+                        "WeakRef<", stringify!(#target), "> refers to a prototype component, ",
+                        "inject it as Deferred<Wrc<", stringify!(#target), ">> instead"
+                    )
+                );
+            };
+            quote::quote! {
+                const _: () = {
+                    use waiter_di::__WaiterNotPrototype;
+                    #check
+                };
+            }
+        });
+
+    quote::quote! { #(#checks)* }
+}
+
 fn generate_inject_deferred(fields: Vec<&Field>, is_tuple: bool) -> TokenStream2 {
     let dependencies_inject: Vec<TokenStream2> = fields.iter()
         .enumerate()
-        .filter(|(_, f)| {
-            if let Type::Path(path_type) = &f.ty {
-                let ptr_type = path_type.path.to_token_stream().to_string();
-
-                return ptr_type.starts_with("waiter :: Deferred <") || ptr_type.starts_with("Deferred <");
-            }
-            false
-        })
+        .filter(|(_, f)| is_deferred(f))
         .map(|(i, f)| if is_tuple {
-            (i, Ident::new(format!("{}", i).as_str(), Span::call_site()))
+            (i, f, Ident::new(format!("{}", i).as_str(), Span::call_site()))
         } else {
-            (i, f.ident.clone().unwrap())
+            (i, f, f.ident.clone().unwrap())
         })
-        .map(|(i, field_name)| {
+        .map(|(i, f, field_name)| {
             let dependency = Ident::new(format!("dep_{}", i).as_str(), Span::call_site());
-            quote::quote! { #field_name.init(#dependency); }
+            let dependency = if is_weak_ref(&f.ty) {
+                quote::quote! { &#dependency }
+            } else {
+                quote::quote! { #dependency }
+            };
+            quote::quote! {
+                #field_name.init(#dependency).map_err(waiter_di::ResolveError::from)?;
+            }
         })
         .collect();
 
@@ -153,24 +263,181 @@ pub(crate) fn generate_dependencies_create_code(args: Vec<TypeToInject>) -> Toke
     }
 }
 
-fn generate_dependency_create_code(to_inject: TypeToInject, pos: usize) -> TokenStream2 {
-    let dep_var_name = quote::format_ident!("dep_{}", pos);
-    let type_path = to_inject.type_path.clone();
+/// Dependencies of `AsyncProvider`, components are awaited before the component that needs them
+pub(crate) fn generate_dependencies_create_code_async(args: &[TypeToInject]) -> TokenStream2 {
+    let container = Ident::new("container", Span::call_site());
+    let dep_code_list: Vec<TokenStream2> = args.iter()
+        .enumerate()
+        .map(|(i, to_inject)| {
+            let dep_var_name = quote::format_ident!("dep_{}", i);
+            let type_path = &to_inject.type_path;
+            let inject_code = injectors().iter()
+                .find_map(|injector| injector.generate_inject_code_async(to_inject, &container))
+                .unwrap_or_else(|| quote::quote! {{
+                    use waiter_di::{__WaiterAsyncFallbackResolve, __WaiterAsyncRuntimeResolve,
+                                    __WaiterAsyncStaticResolve};
+                    *(&&waiter_di::__WaiterResolver::<#type_path>::new()).__waiter_try_create_async(#container).await?
+                }});
+
+            quote::quote! {
+                let #dep_var_name = #inject_code;
+            }
+        })
+        .collect();
+
+    quote::quote! {
+        #(#dep_code_list)*
+    }
+}
 
-    let injectors: Vec<Box<dyn Injector>> = vec![
+#[cfg(feature = "async")]
+fn generate_create_async(
+    comp_name: &Ident,
+    dependencies: &[TypeToInject],
+    factory_code: &TokenStream2,
+) -> TokenStream2 {
+    let dependencies_code = generate_dependencies_create_code_async(dependencies);
+
+    quote::quote! {
+        impl #comp_name {
+            #[doc(hidden)]
+            pub fn __waiter_create_async<P>(
+                container: &mut waiter_di::Container<P>
+            ) -> waiter_di::BoxFuture<'_, Result<Self, waiter_di::ResolveError>> {
+                Box::pin(async move {
+                    #dependencies_code
+                    Ok(#comp_name #factory_code)
+                })
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "async"))]
+fn generate_create_async(
+    _comp_name: &Ident,
+    _dependencies: &[TypeToInject],
+    _factory_code: &TokenStream2,
+) -> TokenStream2 {
+    quote::quote! {}
+}
+
+fn injectors() -> Vec<Box<dyn Injector>> {
+    vec![
         Box::new(DeferredInjector),
+        Box::new(WeakRefInjector),
+        Box::new(LazyInjector),
+        Box::new(FactoryInjector),
+        Box::new(VecInjector),
         Box::new(WrcInjector),
         Box::new(BoxInjector),
         Box::new(ConfigInjector),
+        Box::new(ReloadableInjector),
         Box::new(PropInjector),
-    ];
+    ]
+}
 
-    let inject_code = injectors.iter()
-        .find_map(|injector| injector.generate_inject_code(
-            &to_inject,
+fn find_injector(to_inject: &TypeToInject) -> Option<Box<dyn Injector>> {
+    injectors().into_iter()
+        .find(|injector| injector.generate_inject_code(
+            to_inject,
             &Ident::new("container", Span::call_site()),
-        ))
-        .unwrap_or_else(|| quote::quote! { waiter_di::Provider::<#type_path>::create(container) });
+        ).is_some())
+}
+
+/// Components without a matching injector are created by value
+pub(crate) fn generate_inject_code(to_inject: &TypeToInject, container: &Ident) -> TokenStream2 {
+    let type_path = &to_inject.type_path;
+
+    injectors().iter()
+        .find_map(|injector| injector.generate_inject_code(to_inject, container))
+        .unwrap_or_else(|| quote::quote! { waiter_di::Provider::<#type_path>::try_create(#container)? })
+}
+
+/// Component or interface the dependency refers to, `None` for properties and config
+pub(crate) fn dependency_target(to_inject: &TypeToInject) -> Option<TokenStream2> {
+    match find_injector(to_inject) {
+        Some(injector) => injector.target_type(to_inject),
+        None => Some(to_inject.type_path.to_token_stream()),
+    }
+}
+
+/// Registers dependencies of the component for `Container::dependency_graph()`,
+/// generic components aren't registered
+pub(crate) fn generate_component_info(comp_name: &TokenStream2, dependencies: &[TypeToInject]) -> TokenStream2 {
+    let dependencies_info: Vec<TokenStream2> = dependencies.iter()
+        .enumerate()
+        .map(|(i, to_inject)| {
+            let name = to_inject.arg_name.as_ref()
+                .map(|name| name.to_string())
+                .unwrap_or_else(|| i.to_string());
+            let type_name = to_inject.type_name
+                .replace(" :: ", "::")
+                .replace(" <", "<")
+                .replace("< ", "<")
+                .replace(" >", ">")
+                .replace(" ,", ",");
+            let qualifier = to_inject.qualifier.as_ref()
+                .map(|qualifier| quote::quote! { Some(#qualifier) })
+                .unwrap_or_else(|| quote::quote! { None });
+
+            let injector = find_injector(to_inject);
+
+            let kind = injector.as_ref()
+                .map(|injector| injector.dependency_kind())
+                .unwrap_or_else(|| quote::quote! { waiter_di::DependencyKind::Value });
+            let target = dependency_target(to_inject)
+                .filter(|target| !target.to_string().split(' ').any(|token| token == "Self"))
+                .map(|target| quote::quote! { Some(std::any::type_name::<#target>) })
+                .unwrap_or_else(|| quote::quote! { None });
+            let property = injector.as_ref()
+                .and_then(|injector| injector.property_name(to_inject))
+                .map(|property| quote::quote! { Some(#property) })
+                .unwrap_or_else(|| quote::quote! { None });
+            let check = injector
+                .filter(|injector| injector.property_name(to_inject).is_some())
+                .and_then(|injector| injector.generate_inject_code(
+                    to_inject,
+                    &Ident::new("container", Span::call_site()),
+                ))
+                .map(|inject_code| quote::quote! {
+                    Some(|config| {
+                        let container = waiter_di::__WaiterConfigRef { config };
+                        let _ = #inject_code;
+                        Ok(())
+                    })
+                })
+                .unwrap_or_else(|| quote::quote! { None });
+
+            quote::quote! {
+                waiter_di::DependencyInfo {
+                    name: #name,
+                    type_name: #type_name,
+                    kind: #kind,
+                    qualifier: #qualifier,
+                    target: #target,
+                    property: #property,
+                    check: #check,
+                }
+            }
+        })
+        .collect();
+
+    quote::quote! {
+        const _: () = {
+            waiter_di::inventory::submit! {
+                waiter_di::ComponentInfo {
+                    component: std::any::type_name::<#comp_name>,
+                    dependencies: &[#(#dependencies_info),*],
+                }
+            }
+        };
+    }
+}
+
+fn generate_dependency_create_code(to_inject: TypeToInject, pos: usize) -> TokenStream2 {
+    let dep_var_name = quote::format_ident!("dep_{}", pos);
+    let inject_code = generate_inject_code(&to_inject, &Ident::new("container", Span::call_site()));
 
     quote::quote! {
         let #dep_var_name = #inject_code;
@@ -194,12 +461,20 @@ fn generate_deferred_dependencies_code(fields: Vec<&Field>) -> Result<TokenStrea
                         generic_args = Some(&typ.args);
                     }
                 }
-                if generic_args.is_some() {
-                    if let GenericArgument::Type(typ) = generic_args.unwrap()
+                if let Some(generic_args) = generic_args {
+                    if let GenericArgument::Type(typ) = generic_args
                         .first()
                         .expect("Expected <type> arg for Deferred type")
                     {
-                        return (i, Some(typ));
+                        return (i, Some(typ.clone()));
+                    }
+                }
+
+                if is_weak_ref(&f.ty) {
+                    if let PathArguments::AngleBracketed(typ) = &path_type.path.segments.last().unwrap().arguments {
+                        if let Some(GenericArgument::Type(typ)) = typ.args.first() {
+                            return (i, Some(syn::parse_quote! { Wrc<#typ> }));
+                        }
                     }
                 }
             }
@@ -207,7 +482,11 @@ fn generate_deferred_dependencies_code(fields: Vec<&Field>) -> Result<TokenStrea
         })
         .filter(|(_, opt_arg)| opt_arg.is_some())
         .map(|(i, opt_arg)| (i, opt_arg.unwrap()))
-        .map(|(i, t)| TypeToInject::from_type(t)
+        .map(|(i, t)| TypeToInject::from_type(&t)
+            .and_then(|mut to_inject| {
+                to_inject.qualifier = TypeToInject::from_field(fields[i])?.qualifier;
+                Ok(to_inject)
+            })
             .map(|to_inject| generate_dependency_create_code(to_inject, i))
         )
         .collect::<Result<Vec<_>, Error>>()?;
@@ -215,4 +494,10 @@ fn generate_deferred_dependencies_code(fields: Vec<&Field>) -> Result<TokenStrea
     Ok(quote::quote! {
         #(#dep_code_list)*
     })
-}
\ No newline at end of file
+}
+
+/// `WeakRef` is filled with a weak pointer to the singleton after the component is created
+fn is_weak_ref(field_type: &Type) -> bool {
+    let type_name = field_type.to_token_stream().to_string();
+    type_name.starts_with("WeakRef <") || type_name.starts_with("waiter_di :: WeakRef <")
+}
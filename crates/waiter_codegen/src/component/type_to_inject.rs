@@ -3,7 +3,7 @@ use quote::ToTokens;
 use syn::{Attribute, Error, Field, FnArg, Pat, Path, Type};
 use syn::spanned::Spanned;
 
-use crate::attr_parser::{parse_prop_attr, PropAttr};
+use crate::attr_parser::{parse_inject_attr, parse_prop_attr, PropAttr};
 
 #[derive(Clone)]
 pub(crate) struct TypeToInject {
@@ -11,6 +11,7 @@ pub(crate) struct TypeToInject {
     pub(crate) type_path: Path,
     pub(crate) arg_name: Option<TokenStream2>,
     pub(crate) prop_attr: Option<PropAttr>,
+    pub(crate) qualifier: Option<String>,
 }
 
 
@@ -21,6 +22,7 @@ impl TypeToInject {
             type_path: Self::parse_path(type_)?,
             arg_name: None,
             prop_attr: None,
+            qualifier: None,
         })
     }
     pub(crate) fn from_field(field: &Field) -> Result<Self, Error> {
@@ -29,6 +31,7 @@ impl TypeToInject {
             type_path: Self::parse_path(&field.ty)?,
             arg_name: field.ident.clone().map(|name| name.to_token_stream()),
             prop_attr: Self::parse_attr(&field.attrs)?,
+            qualifier: Self::parse_qualifier(&field.attrs)?,
         })
     }
     pub(crate) fn from_fn_arg(arg: FnArg) -> Result<Self, Error> {
@@ -49,6 +52,7 @@ impl TypeToInject {
             type_path: Self::parse_path(&typed.ty)?,
             arg_name,
             prop_attr: Self::parse_attr(&typed.attrs)?,
+            qualifier: Self::parse_qualifier(&typed.attrs)?,
         })
     }
 
@@ -64,6 +68,13 @@ impl TypeToInject {
         Ok(None)
     }
 
+    fn parse_qualifier(attrs: &[Attribute]) -> Result<Option<String>, Error> {
+        attrs.iter()
+            .find(|attr| attr.path.to_token_stream().to_string() == *"inject")
+            .map(parse_inject_attr)
+            .transpose()
+    }
+
     fn parse_path(type_: &Type) -> Result<Path, Error> {
         if let Type::Path(path_type) = type_ {
             Ok(path_type.path.clone())
@@ -0,0 +1,68 @@
+use proc_macro::TokenStream;
+
+use syn::{Error, ItemStruct};
+use syn::spanned::Spanned;
+
+use crate::attr_parser::{ComponentScope, ConfigPropertiesAttr};
+use crate::provider::generate_component_provider_impl;
+
+/// Singleton deserialized from the config section, it's validated like a `#[prop]` of this type
+pub(crate) fn generate_config_properties(
+    attr: ConfigPropertiesAttr,
+    properties: ItemStruct,
+) -> Result<TokenStream, Error> {
+    if !properties.generics.params.is_empty() {
+        return Err(Error::new(
+            properties.generics.span(),
+            "#[config_properties] can't be used on generic structs",
+        ));
+    }
+
+    let name = &properties.ident;
+    let type_name = name.to_string();
+    let prefix = attr.prefix;
+    let dependency_name = if prefix.is_empty() { "config".to_string() } else { prefix.clone() };
+    let property = if prefix.is_empty() {
+        quote::quote! { None }
+    } else {
+        quote::quote! { Some(#prefix) }
+    };
+
+    let create_code = quote::quote! {
+        waiter_di::read_properties::<#name>(&self.config, #prefix)
+    };
+    let create_code_async = quote::quote! {
+        std::future::ready(waiter_di::read_properties::<#name>(&self.config, #prefix))
+    };
+
+    let mut result = generate_component_provider_impl(
+        quote::quote! { #name },
+        vec!(),
+        vec!(),
+        create_code,
+        Some(create_code_async),
+        None,
+        ComponentScope::Singleton,
+    );
+    result.extend(TokenStream::from(quote::quote! {
+        const _: () = {
+            waiter_di::inventory::submit! {
+                waiter_di::ComponentInfo {
+                    component: std::any::type_name::<#name>,
+                    dependencies: &[waiter_di::DependencyInfo {
+                        name: #dependency_name,
+                        type_name: #type_name,
+                        kind: waiter_di::DependencyKind::Prop,
+                        qualifier: None,
+                        target: None,
+                        property: #property,
+                        check: Some(|config| {
+                            waiter_di::read_properties::<#name>(config, #prefix).map(|_| ())
+                        }),
+                    }],
+                }
+            }
+        };
+    }));
+    Ok(result)
+}
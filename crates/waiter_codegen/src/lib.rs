@@ -7,35 +7,43 @@ use syn::punctuated::Punctuated;
 use syn::spanned::Spanned;
 use syn::token::Comma;
 
-use attr_parser::parse_provides_attr;
+use attr_parser::{parse_component_attr, parse_config_properties_attr, parse_provides_attr, ComponentScope};
 use component::{generate_component_for_impl, generate_component_for_struct};
+use config_properties::generate_config_properties;
+use lifecycle::{generate_post_construct, generate_pre_destroy};
 use provider::*;
 
 
 mod component;
+mod config_properties;
+mod lifecycle;
 mod provider;
 mod attr_parser;
 
 #[proc_macro_attribute]
-pub fn module(_attr: TokenStream, item: TokenStream) -> TokenStream {
-    component(_attr, item)
+pub fn module(attr: TokenStream, item: TokenStream) -> TokenStream {
+    component(attr, item)
 }
 
 #[proc_macro_attribute]
-pub fn component(_attr: TokenStream, item: TokenStream) -> TokenStream {
+pub fn component(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let component_attr = match parse_component_attr(attr) {
+        Ok(attr) => attr,
+        Err(error) => return error.to_compile_error().into()
+    };
+
     let mut res: TokenStream = remove_attrs(item.clone());
 
     let comp = syn::parse::<ItemStruct>(item.clone());
-    if comp.is_ok() {
-        let comp = comp.unwrap();
+    if let Ok(comp) = comp {
         res.extend(unwrap(generate_component_for_struct(comp.clone())));
-        res.extend(generate_component_provider_impl_struct(comp.clone()));
+        res.extend(generate_component_provider_impl_struct(comp.clone(), component_attr.scope));
         return res;
     }
 
     let impl_block = syn::parse::<ItemImpl>(item.clone())
         .expect("#[component]/#[module] cant be used only on struct or impls");
-    res.extend(unwrap(generate_component_for_impl(impl_block.clone())));
+    res.extend(unwrap(generate_component_for_impl(impl_block.clone(), component_attr.scope)));
     res
 }
 
@@ -60,10 +68,37 @@ pub fn provides(attr: TokenStream, item: TokenStream) -> TokenStream {
         provides_attr,
         fn_block.clone(),
         TokenStream2::new(),
+        ComponentScope::Singleton,
     )));
     res
 }
 
+#[proc_macro_attribute]
+pub fn config_properties(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let config_properties_attr = match parse_config_properties_attr(attr) {
+        Ok(attr) => attr,
+        Err(error) => return error.to_compile_error().into()
+    };
+
+    let mut res = item.clone();
+    let properties = syn::parse::<ItemStruct>(item)
+        .expect("#[config_properties] must be used only on structs");
+    res.extend(unwrap(generate_config_properties(config_properties_attr, properties)));
+    res
+}
+
+#[proc_macro_attribute]
+pub fn post_construct(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let method = parse_macro_input!(item as ImplItemMethod);
+    unwrap(generate_post_construct(method))
+}
+
+#[proc_macro_attribute]
+pub fn pre_destroy(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let method = parse_macro_input!(item as ImplItemMethod);
+    unwrap(generate_pre_destroy(method))
+}
+
 #[proc_macro_attribute]
 pub fn wrapper(_attr: TokenStream, item: TokenStream) -> TokenStream {
     let wrapper = parse_macro_input!(item as ItemStruct);
@@ -103,6 +138,7 @@ fn remove_attrs(item: TokenStream) -> TokenStream {
     fn attr_filter(attr: &Attribute) -> bool {
         let attr_name = attr.path.to_token_stream().to_string();
         attr_name.as_str() != "prop" && attr_name.as_str() != "provides"
+            && attr_name.as_str() != "inject"
     }
 
     let item = syn::parse::<Item>(item).unwrap();
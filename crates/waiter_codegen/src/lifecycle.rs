@@ -0,0 +1,114 @@
+use proc_macro::TokenStream;
+
+use syn::{Error, FnArg, ImplItemMethod, ReturnType};
+use syn::spanned::Spanned;
+
+pub(crate) fn generate_post_construct(method: ImplItemMethod) -> Result<TokenStream, Error> {
+    if method.sig.asyncness.is_some() && !cfg!(feature = "async") {
+        return Err(Error::new(
+            method.sig.asyncness.span(),
+            "async #[post_construct] requires the `async` feature of waiter_di",
+        ));
+    }
+    check_hook_signature(&method, "#[post_construct]", true)?;
+
+    let hook = &method.sig.ident;
+    let call_code = if let ReturnType::Default = method.sig.output {
+        quote::quote! {
+            self.#hook();
+            Ok(())
+        }
+    } else {
+        quote::quote! { self.#hook().map_err(waiter_di::ResolveError::post_construct) }
+    };
+
+    if method.sig.asyncness.is_some() {
+        let call_code = if let ReturnType::Default = method.sig.output {
+            quote::quote! {
+                self.#hook().await;
+                Ok(())
+            }
+        } else {
+            quote::quote! { self.#hook().await.map_err(waiter_di::ResolveError::post_construct) }
+        };
+
+        return Ok(quote::quote! {
+            #method
+            #[doc(hidden)]
+            pub fn __waiter_post_construct(&self) -> Result<(), waiter_di::ResolveError> {
+                Err(waiter_di::ResolveError::async_component(std::any::type_name::<Self>()))
+            }
+            #[doc(hidden)]
+            pub fn __waiter_post_construct_async(
+                &self
+            ) -> waiter_di::BoxFuture<'_, Result<(), waiter_di::ResolveError>> {
+                Box::pin(async move { #call_code })
+            }
+        }.into());
+    }
+
+    let async_code = if cfg!(feature = "async") {
+        quote::quote! {
+            #[doc(hidden)]
+            pub fn __waiter_post_construct_async(
+                &self
+            ) -> waiter_di::BoxFuture<'_, Result<(), waiter_di::ResolveError>> {
+                Box::pin(std::future::ready(self.__waiter_post_construct()))
+            }
+        }
+    } else {
+        quote::quote! {}
+    };
+
+    Ok(quote::quote! {
+        #method
+        #[doc(hidden)]
+        pub fn __waiter_post_construct(&self) -> Result<(), waiter_di::ResolveError> {
+            #call_code
+        }
+        #async_code
+    }.into())
+}
+
+pub(crate) fn generate_pre_destroy(method: ImplItemMethod) -> Result<TokenStream, Error> {
+    check_hook_signature(&method, "#[pre_destroy]", false)?;
+
+    let hook = &method.sig.ident;
+    let call_code = if let ReturnType::Default = method.sig.output {
+        quote::quote! { self.#hook(); }
+    } else {
+        quote::quote! {
+            if let Err(error) = self.#hook() {
+                waiter_di::log::error!("Pre destroy of {} failed: {}", std::any::type_name::<Self>(), error);
+            }
+        }
+    };
+
+    Ok(quote::quote! {
+        #method
+        #[doc(hidden)]
+        pub fn __waiter_pre_destroy(&self) {
+            #call_code
+        }
+    }.into())
+}
+
+fn check_hook_signature(method: &ImplItemMethod, attr: &str, allow_async: bool) -> Result<(), Error> {
+    let is_ref_self = matches!(
+        method.sig.inputs.first(),
+        Some(FnArg::Receiver(receiver)) if receiver.reference.is_some() && receiver.mutability.is_none()
+    );
+    if method.sig.asyncness.is_some() && !allow_async {
+        return Err(Error::new(
+            method.sig.asyncness.span(),
+            format!("{} method can't be async", attr),
+        ));
+    }
+    if !is_ref_self || method.sig.inputs.len() != 1 {
+        return Err(Error::new(
+            method.sig.span(),
+            format!("{} method must have the only `&self` argument", attr),
+        ));
+    }
+    Ok(())
+}
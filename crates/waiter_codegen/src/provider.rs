@@ -1,24 +1,38 @@
 use proc_macro::TokenStream;
 use std::ops::Deref;
+use std::sync::{Mutex, PoisonError};
 
 use proc_macro2::TokenStream as TokenStream2;
 use quote::ToTokens;
-use syn::{Error, GenericParam, ItemFn, ItemImpl, ItemStruct, Path, ReturnType, Type};
+use syn::{Error, GenericArgument, GenericParam, Ident, ItemFn, ItemImpl, ItemStruct, Path, PathArguments,
+          ReturnType, Type};
 use syn::spanned::Spanned;
 
-use crate::attr_parser::ProvidesAttr;
-use crate::component::{generate_dependencies_create_code, generate_inject_dependencies_tuple};
+use crate::attr_parser::{qualifier_id, ComponentScope, ProvidesAttr};
+use crate::component::{generate_component_info, generate_dependencies_create_code,
+                       generate_dependencies_create_code_async, generate_inject_dependencies_tuple,
+                       has_deferred_fields};
 use crate::component::type_to_inject::TypeToInject;
 
-pub(crate) fn generate_component_provider_impl_struct(component: ItemStruct) -> TokenStream {
+pub(crate) fn generate_component_provider_impl_struct(
+    component: ItemStruct,
+    scope: ComponentScope,
+) -> TokenStream {
     let comp_name = component.ident;
     let comp_generics = component.generics.clone();
 
     let create_component_code = quote::quote! {
         #comp_name::__waiter_create(self)
     };
-    let inject_deferred_code = quote::quote! {
-        #comp_name::__waiter_inject_deferred(self, &component);
+    let create_component_code_async = if comp_generics.params.is_empty() {
+        Some(quote::quote! { #comp_name::__waiter_create_async(self) })
+    } else {
+        None
+    };
+    let inject_deferred_code = if has_deferred_fields(&component.fields) {
+        Some(quote::quote! { #comp_name::__waiter_inject_deferred(container, &component) })
+    } else {
+        None
     };
 
     generate_component_provider_impl(
@@ -26,7 +40,9 @@ pub(crate) fn generate_component_provider_impl_struct(component: ItemStruct) ->
         component.generics.params.iter().collect(),
         vec!(),
         create_component_code,
+        create_component_code_async,
         inject_deferred_code,
+        scope,
     )
 }
 
@@ -34,10 +50,21 @@ pub(crate) fn generate_component_provider_impl_fn(
     provides: ProvidesAttr,
     factory: ItemFn,
     force_type: TokenStream2,
+    scope: ComponentScope,
 ) -> Result<TokenStream, Error> {
+    if provides.is_conditional() {
+        return Err(Error::new(
+            factory.span(),
+            "Conditions are supported only for impl blocks of traits",
+        ));
+    }
+
+    let result_ok_type = get_result_ok_type(&factory.sig.output);
+
     let comp_name = if force_type.is_empty() {
         let ret_value = if let ReturnType::Type(_, type_) = &factory.sig.output {
-            if let Type::Path(type_path) = type_.deref() {
+            let type_ = result_ok_type.unwrap_or(type_.deref());
+            if let Type::Path(type_path) = type_ {
                 type_path.path.segments.to_token_stream()
             } else {
                 return Err(Error::new(
@@ -63,31 +90,96 @@ pub(crate) fn generate_component_provider_impl_fn(
         quote::quote! { #force_type :: }
     };
 
-    let dependencies_code = generate_dependencies_create_code(
-        factory.sig.inputs.iter()
-            .map(|arg| TypeToInject::from_fn_arg(arg.clone()))
-            .collect::<Result<Vec<_>, _>>()?
-    );
+    let dependencies = factory.sig.inputs.iter()
+        .map(|arg| TypeToInject::from_fn_arg(arg.clone()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let component_info = if factory.sig.generics.params.is_empty() {
+        generate_component_info(&comp_name, &dependencies)
+    } else {
+        quote::quote! {}
+    };
+    if factory.sig.asyncness.is_some() && !cfg!(feature = "async") {
+        return Err(Error::new(
+            factory.sig.asyncness.span(),
+            "async factories require the `async` feature of waiter_di",
+        ));
+    }
+    let is_async = factory.sig.asyncness.is_some();
+    let dependencies_code_async = generate_dependencies_create_code_async(&dependencies);
+    let dependencies_code = generate_dependencies_create_code(dependencies);
     let factory_code = generate_inject_dependencies_tuple(factory.sig.inputs.len());
+    let await_code = if is_async {
+        quote::quote! { .await }
+    } else {
+        quote::quote! {}
+    };
+    let factory_call_code = if result_ok_type.is_some() {
+        quote::quote! { #fn_name_prefix #fn_name #factory_code #await_code.map_err(waiter_di::ResolveError::factory) }
+    } else {
+        quote::quote! { Ok(#fn_name_prefix #fn_name #factory_code #await_code) }
+    };
 
-    let create_component_code = quote::quote! {
-        {
-            let container = &mut *self;
-            #dependencies_code
-            #fn_name_prefix #fn_name #factory_code
+    // Singletons created asynchronously are still returned by the synchronous provider
+    let create_component_code = if is_async {
+        quote::quote! {
+            Err::<#comp_name, waiter_di::ResolveError>(
+                waiter_di::ResolveError::async_component(std::any::type_name::<#comp_name>())
+            )
+        }
+    } else {
+        quote::quote! {
+            (|container: &mut Self| -> Result<#comp_name, waiter_di::ResolveError> {
+                #dependencies_code
+                #factory_call_code
+            })(self)
         }
     };
-    let inject_deferred_code = quote::quote! {};
-
-    Ok(generate_component_provider_impl(
+    let create_component_code_async = if factory.sig.generics.params.is_empty() {
+        Some(quote::quote! {
+            async {
+                let container = &mut *self;
+                #dependencies_code_async
+                #factory_call_code
+            }
+        })
+    } else {
+        None
+    };
+    let mut result = generate_component_provider_impl(
         comp_name,
         factory.sig.generics.params.iter()
             .filter(|p| matches!(p, GenericParam::Lifetime(_)))
             .collect(),
         provides.profiles,
         create_component_code,
-        inject_deferred_code,
-    ))
+        create_component_code_async,
+        None,
+        scope,
+    );
+    result.extend(TokenStream::from(component_info));
+    Ok(result)
+}
+
+fn get_result_ok_type(output: &ReturnType) -> Option<&Type> {
+    let type_path = match output {
+        ReturnType::Type(_, type_) => match type_.deref() {
+            Type::Path(type_path) => type_path,
+            _ => return None
+        },
+        ReturnType::Default => return None
+    };
+
+    let last_segment = type_path.path.segments.last()?;
+    if last_segment.ident != "Result" {
+        return None;
+    }
+
+    if let PathArguments::AngleBracketed(args) = &last_segment.arguments {
+        if let Some(GenericArgument::Type(ok_type)) = args.args.first() {
+            return Some(ok_type);
+        }
+    }
+    None
 }
 
 pub fn generate_component_provider_impl(
@@ -95,8 +187,15 @@ pub fn generate_component_provider_impl(
     comp_generics: Vec<&GenericParam>,
     profiles: Vec<Path>,
     create_component_code: TokenStream2,
-    inject_deferred_code: TokenStream2,
+    create_component_code_async: Option<TokenStream2>,
+    inject_deferred_code: Option<TokenStream2>,
+    scope: ComponentScope,
 ) -> TokenStream {
+    // Only singletons with `Deferred` dependencies wait for the outermost component
+    let has_deferred = inject_deferred_code.is_some();
+    let inject_deferred_code = inject_deferred_code
+        .unwrap_or_else(|| quote::quote! { Ok::<(), waiter_di::ResolveError>(()) });
+    let is_profile_specific = !profiles.is_empty();
     let (profiles, provider_generics) = if profiles.is_empty() {
         let generic_profile = quote::quote! { PROFILE };
 
@@ -114,34 +213,381 @@ pub fn generate_component_provider_impl(
         (profiles, quote::quote! { <#(#comp_generics),*> })
     };
 
+    let pre_destroy_code = quote::quote! {
+        |component| {
+            use waiter_di::__WaiterLifecycle;
+            if let Some(component) = component.downcast_ref::<#comp_name>() {
+                component.__waiter_pre_destroy();
+            }
+        }
+    };
+
+    let async_provider_code = create_component_code_async
+        .map(|create_component_code_async| generate_async_provider_impl(
+            &comp_name,
+            &profiles,
+            &provider_generics,
+            create_component_code_async,
+            &inject_deferred_code,
+            has_deferred,
+            &scope,
+            &pre_destroy_code,
+        ))
+        .unwrap_or_default();
+
+    // Components provided only for some profiles aren't resolved statically by components
+    // generic over the profile, so they are registered to be selected at runtime
+    let bindings_code = if is_profile_specific && comp_generics.is_empty() {
+        generate_profile_bindings(&comp_name, &profiles)
+    } else {
+        quote::quote! {}
+    };
+
+    let prototype_code = if let ComponentScope::Prototype = scope {
+        quote::quote! {
+            impl <#(#comp_generics),*> waiter_di::__WaiterPrototype for #comp_name {}
+        }
+    } else {
+        quote::quote! {}
+    };
+
+    let get_ref_code = if let ComponentScope::Singleton = scope {
+        quote::quote! {
+            fn try_get_ref(&mut self) -> Result<&Self::Impl, waiter_di::ResolveError> {
+                let component = <Self as waiter_di::Provider<#comp_name>>::try_get(self)?;
+                // Singleton or its override is still stored in the container, so it can be safely
+                // returned as a reference that has the same life as the container reference
+                unsafe {
+                    Ok(&*waiter_di::Wrc::as_ptr(&component))
+                }
+            }
+        }
+    } else {
+        quote::quote! {}
+    };
+
+    let create_component_code = generate_construct_code(&comp_name, create_component_code);
+
+    let get_component_code = match scope {
+        ComponentScope::Singleton => quote::quote! {
+            let type_id = std::any::TypeId::of::<#comp_name>();
+            let type_name = std::any::type_name::<#comp_name>();
+            let _construction = self.__waiter_lock_construction();
+            if !self.components().contains_key(&type_id) {
+                self.__waiter_begin_singleton(type_name);
+                let created = #create_component_code;
+                self.__waiter_end_singleton();
+                let component = waiter_di::Wrc::new(created.map_err(|error| error.within(type_name))?);
+                self.components().insert(type_id, component.clone());
+                self.__waiter_initialize_singleton(
+                    type_id,
+                    type_name,
+                    component,
+                    |container, component| {
+                        let component = component.clone()
+                            .downcast::<#comp_name>()
+                            .map_err(|_| waiter_di::ResolveError::type_mismatch(
+                                std::any::type_name::<#comp_name>()
+                            ))?;
+                        container.__waiter_with_profile(|container: &mut Self| {
+                            #inject_deferred_code.and_then(|_| {
+                                use waiter_di::__WaiterLifecycle;
+                                (*component).__waiter_post_construct()
+                            })
+                        })
+                    },
+                    #pre_destroy_code,
+                    #has_deferred,
+                )?;
+            }
+            let any = self.components().get(&type_id)
+                .unwrap();
+
+            return any.clone()
+                .downcast::<#comp_name>()
+                .map_err(|_| waiter_di::ResolveError::type_mismatch(type_name));
+        },
+        ComponentScope::Prototype => quote::quote! {
+            return <Self as waiter_di::Provider<#comp_name>>::try_create(self).map(waiter_di::Wrc::new);
+        },
+        ComponentScope::Custom(scope) => quote::quote! {
+            let type_id = std::any::TypeId::of::<#comp_name>();
+            let type_name = std::any::type_name::<#comp_name>();
+            self.__waiter_ensure_scope_active::<#scope>().map_err(|error| error.within(type_name))?;
+            let cached = waiter_di::Scope::get(self.scope_mut::<#scope>(), type_id);
+            if let Some(component) = cached {
+                return component
+                    .downcast::<#comp_name>()
+                    .map_err(|_| waiter_di::ResolveError::type_mismatch(type_name));
+            }
+
+            let component = waiter_di::Wrc::new(
+                #create_component_code.map_err(|error| error.within(type_name))?
+            );
+            waiter_di::Scope::put(self.scope_mut::<#scope>(), type_id, component.clone());
+            let initialized = (|container: &mut Self| #inject_deferred_code)(self).and_then(|_| {
+                use waiter_di::__WaiterLifecycle;
+                (*component).__waiter_post_construct()
+            });
+            if let Err(error) = initialized {
+                waiter_di::Scope::remove(self.scope_mut::<#scope>(), type_id);
+                return Err(error.within(type_name));
+            }
+            waiter_di::Scope::initialized(self.scope_mut::<#scope>(), type_id, #pre_destroy_code);
+            return Ok(component);
+        },
+    };
+
     let result = quote::quote! {#(
         impl #provider_generics waiter_di::Provider<#comp_name> for waiter_di::Container<#profiles> {
             type Impl = #comp_name;
-            fn get(&mut self) -> waiter_di::Wrc<Self::Impl> {
-                let type_id = std::any::TypeId::of::<#comp_name>();
-                if !self.components.contains_key(&type_id) {
-                    let component = waiter_di::Wrc::new(#create_component_code);
-                    self.components.insert(type_id, component.clone());
-                    #inject_deferred_code
+            #get_ref_code
+            fn try_get(&mut self) -> Result<waiter_di::Wrc<Self::Impl>, waiter_di::ResolveError> {
+                self.__waiter_ensure_open()?;
+                if let Some(component) = self.overridden::<#comp_name>() {
+                    return Ok(component);
                 }
-                let any = self.components.get(&type_id)
-                    .unwrap();
 
-                return any.clone()
-                    .downcast::<#comp_name>()
-                    .unwrap();
+                #get_component_code
             }
-            fn create(&mut self) -> Self::Impl {
-                let component = #create_component_code;
-                #inject_deferred_code
-                return component;
+            fn try_create(&mut self) -> Result<Self::Impl, waiter_di::ResolveError> {
+                self.__waiter_ensure_open()?;
+                if let Some(component) = self.overridden_create::<#comp_name>() {
+                    return Ok(*component);
+                }
+
+                let type_name = std::any::type_name::<#comp_name>();
+                let component = #create_component_code.map_err(|error| error.within(type_name))?;
+                (|container: &mut Self| #inject_deferred_code)(self)
+                    .and_then(|_| {
+                        use waiter_di::__WaiterLifecycle;
+                        component.__waiter_post_construct()
+                    })
+                    .map_err(|error| error.within(type_name))?;
+                return Ok(component);
             }
         }
-    )*};
+    )*
+        #async_provider_code
+        #bindings_code
+        #prototype_code
+    };
 
     TokenStream::from(result)
 }
 
+fn generate_profile_bindings(comp_name: &TokenStream2, profiles: &[TokenStream2]) -> TokenStream2 {
+    let bindings = profiles.iter().map(|profile| quote::quote! {
+        const _: () = {
+            fn get(
+                container: &mut waiter_di::Container<()>
+            ) -> Result<Box<dyn std::any::Any>, waiter_di::ResolveError> {
+                container.__waiter_with_profile(|container: &mut waiter_di::Container<#profile>| {
+                    waiter_di::Provider::<#comp_name>::try_get(container)
+                })
+                    .map(|component| Box::new(component) as Box<dyn std::any::Any>)
+            }
+            fn create(
+                container: &mut waiter_di::Container<()>
+            ) -> Result<Box<dyn std::any::Any>, waiter_di::ResolveError> {
+                container.__waiter_with_profile(|container: &mut waiter_di::Container<#profile>| {
+                    waiter_di::Provider::<#comp_name>::try_create(container).map(Box::new)
+                })
+                    .map(|component| Box::new(component) as Box<dyn std::any::Any>)
+            }
+
+            waiter_di::inventory::submit! {
+                waiter_di::InterfaceBinding {
+                    interface: std::any::TypeId::of::<#comp_name>,
+                    interface_name: std::any::type_name::<#comp_name>,
+                    component: std::any::type_name::<#comp_name>,
+                    profile: Some(std::any::type_name::<#profile>),
+                    order: None,
+                    name: None,
+                    conditions: &[],
+                    get,
+                    create,
+                }
+            }
+        };
+    });
+
+    quote::quote! { #(#bindings)* }
+}
+
+fn generate_construct_code(comp_name: &TokenStream2, create_component_code: TokenStream2) -> TokenStream2 {
+    quote::quote! {
+        match self.__waiter_begin_construct(
+            std::any::TypeId::of::<#comp_name>(),
+            std::any::type_name::<#comp_name>(),
+        ) {
+            Ok(()) => {
+                let created = #create_component_code;
+                self.__waiter_end_construct(created.is_err());
+                created
+            }
+            Err(error) => Err(error)
+        }
+    }
+}
+
+/// `AsyncProvider` awaits the component and its async `#[post_construct]` hook. `Deferred`
+/// dependencies are injected synchronously before the hook is called, so hooks of nested
+/// singletons with them are awaited after the outermost component is created
+#[cfg(feature = "async")]
+#[allow(clippy::too_many_arguments)]
+fn generate_async_provider_impl(
+    comp_name: &TokenStream2,
+    profiles: &[TokenStream2],
+    provider_generics: &TokenStream2,
+    create_component_code: TokenStream2,
+    inject_deferred_code: &TokenStream2,
+    has_deferred: bool,
+    scope: &ComponentScope,
+    pre_destroy_code: &TokenStream2,
+) -> TokenStream2 {
+    let create_component_code = generate_construct_code(
+        comp_name,
+        quote::quote! { #create_component_code.await },
+    );
+
+    let get_component_code = match scope {
+        ComponentScope::Singleton => quote::quote! {
+            let type_id = std::any::TypeId::of::<#comp_name>();
+            let type_name = std::any::type_name::<#comp_name>();
+            if !self.components().contains_key(&type_id) {
+                self.__waiter_begin_singleton(type_name);
+                let created = #create_component_code;
+                self.__waiter_end_singleton();
+                let component = waiter_di::Wrc::new(created.map_err(|error| error.within(type_name))?);
+                self.components().insert(type_id, component.clone());
+                self.__waiter_initialize_singleton(
+                    type_id,
+                    type_name,
+                    component.clone(),
+                    |container, component| {
+                        let component = component.clone()
+                            .downcast::<#comp_name>()
+                            .map_err(|_| waiter_di::ResolveError::type_mismatch(
+                                std::any::type_name::<#comp_name>()
+                            ))?;
+                        container.__waiter_with_profile(|container: &mut Self| #inject_deferred_code)
+                    },
+                    #pre_destroy_code,
+                    #has_deferred,
+                )?;
+                self.__waiter_post_construct_singleton_async(
+                    type_id,
+                    type_name,
+                    component,
+                    |component| Box::pin(async move {
+                        let component = component
+                            .downcast::<#comp_name>()
+                            .map_err(|_| waiter_di::ResolveError::type_mismatch(
+                                std::any::type_name::<#comp_name>()
+                            ))?;
+                        use waiter_di::__WaiterLifecycle;
+                        (*component).__waiter_post_construct_async().await
+                    }),
+                ).await?;
+            }
+            let any = self.components().get(&type_id)
+                .unwrap();
+
+            return any.clone()
+                .downcast::<#comp_name>()
+                .map_err(|_| waiter_di::ResolveError::type_mismatch(type_name));
+        },
+        ComponentScope::Prototype => quote::quote! {
+            return <Self as waiter_di::AsyncProvider<#comp_name>>::try_create_async(self).await
+                .map(waiter_di::Wrc::new);
+        },
+        ComponentScope::Custom(scope) => quote::quote! {
+            let type_id = std::any::TypeId::of::<#comp_name>();
+            let type_name = std::any::type_name::<#comp_name>();
+            self.__waiter_ensure_scope_active::<#scope>().map_err(|error| error.within(type_name))?;
+            let cached = waiter_di::Scope::get(self.scope_mut::<#scope>(), type_id);
+            if let Some(component) = cached {
+                return component
+                    .downcast::<#comp_name>()
+                    .map_err(|_| waiter_di::ResolveError::type_mismatch(type_name));
+            }
+
+            let component = waiter_di::Wrc::new(
+                #create_component_code.map_err(|error| error.within(type_name))?
+            );
+            waiter_di::Scope::put(self.scope_mut::<#scope>(), type_id, component.clone());
+            let initialized = match (|container: &mut Self| #inject_deferred_code)(self) {
+                Ok(()) => {
+                    use waiter_di::__WaiterLifecycle;
+                    (*component).__waiter_post_construct_async().await
+                }
+                Err(error) => Err(error)
+            };
+            if let Err(error) = initialized {
+                waiter_di::Scope::remove(self.scope_mut::<#scope>(), type_id);
+                return Err(error.within(type_name));
+            }
+            waiter_di::Scope::initialized(self.scope_mut::<#scope>(), type_id, #pre_destroy_code);
+            return Ok(component);
+        },
+    };
+
+    quote::quote! {#(
+        impl #provider_generics waiter_di::AsyncProvider<#comp_name> for waiter_di::Container<#profiles> {
+            fn try_get_async(
+                &mut self
+            ) -> waiter_di::BoxFuture<'_, Result<waiter_di::Wrc<Self::Impl>, waiter_di::ResolveError>> {
+                Box::pin(async move {
+                    self.__waiter_ensure_open()?;
+                    if let Some(component) = self.overridden::<#comp_name>() {
+                        return Ok(component);
+                    }
+
+                    #get_component_code
+                })
+            }
+            fn try_create_async(
+                &mut self
+            ) -> waiter_di::BoxFuture<'_, Result<Self::Impl, waiter_di::ResolveError>> {
+                Box::pin(async move {
+                    self.__waiter_ensure_open()?;
+                    if let Some(component) = self.overridden_create::<#comp_name>() {
+                        return Ok(*component);
+                    }
+
+                    let type_name = std::any::type_name::<#comp_name>();
+                    let component = #create_component_code.map_err(|error| error.within(type_name))?;
+                    let initialized = match (|container: &mut Self| #inject_deferred_code)(self) {
+                        Ok(()) => {
+                            use waiter_di::__WaiterLifecycle;
+                            component.__waiter_post_construct_async().await
+                        }
+                        Err(error) => Err(error)
+                    };
+                    initialized.map_err(|error| error.within(type_name))?;
+                    return Ok(component);
+                })
+            }
+        }
+    )*}
+}
+
+#[cfg(not(feature = "async"))]
+#[allow(clippy::too_many_arguments)]
+fn generate_async_provider_impl(
+    _comp_name: &TokenStream2,
+    _profiles: &[TokenStream2],
+    _provider_generics: &TokenStream2,
+    _create_component_code: TokenStream2,
+    _inject_deferred_code: &TokenStream2,
+    _has_deferred: bool,
+    _scope: &ComponentScope,
+    _pre_destroy_code: &TokenStream2,
+) -> TokenStream2 {
+    quote::quote! {}
+}
+
 pub(crate) fn generate_interface_provider_impl(provides: ProvidesAttr, impl_block: ItemImpl) -> TokenStream {
     let interface = match impl_block.trait_ {
         Some((_, interface, _)) => interface,
@@ -157,26 +603,256 @@ pub(crate) fn generate_interface_provider_impl(provides: ProvidesAttr, impl_bloc
         return TokenStream::from(Error::new(impl_block.self_ty.span(), "Failed to create provider").to_compile_error());
     };
 
+    if provides.name.is_some() && provides.is_conditional() {
+        return TokenStream::from(Error::new(
+            comp_name.span(),
+            "Named components can't be provided conditionally",
+        ).to_compile_error());
+    }
+
+    let bindings_code = generate_interface_bindings(&provides, &interface, &comp_name);
+    let (provider, upcast, async_provider) = match &provides.name {
+        Some(name) => {
+            let id = qualifier_id(name);
+            (quote::quote! { waiter_di::NamedProvider<dyn #interface, #id> }, None, None)
+        }
+        None if provides.order.is_some() || provides.is_conditional() =>
+            return TokenStream::from(bindings_code),
+        None if !is_static_provider(&interface, &provides.profiles, &comp_name) =>
+            return TokenStream::from(bindings_code),
+        None => (
+            quote::quote! { waiter_di::Provider<dyn #interface> },
+            Some(quote::quote! { waiter_di::__WaiterUpcast<dyn #interface> }),
+            Some(quote::quote! { waiter_di::AsyncProvider<dyn #interface> }),
+        )
+    };
+
+    // The override of the trait can't be returned as the implementation, so it's reported
+    // instead of silently resolving the real component
+    // Other implementations of the trait without `order` are only registered, so they make it
+    // ambiguous at runtime
+    let (ensure_not_overridden, ensure_create_not_overridden) = if provides.name.is_none() {
+        (
+            quote::quote! {
+                self.__waiter_ensure_not_overridden::<dyn #interface>(false)?;
+                self.__waiter_ensure_single_provider::<dyn #interface>()?;
+            },
+            quote::quote! {
+                self.__waiter_ensure_not_overridden::<dyn #interface>(true)?;
+                self.__waiter_ensure_single_provider::<dyn #interface>()?;
+            },
+        )
+    } else {
+        (quote::quote! {}, quote::quote! {})
+    };
+
+    let get_ref_code = if provides.name.is_none() {
+        quote::quote! {
+            fn try_get_ref(&mut self) -> Result<&Self::Impl, waiter_di::ResolveError> {
+                #ensure_not_overridden
+                waiter_di::Provider::<#comp_name>::try_get_ref(self)
+            }
+        }
+    } else {
+        quote::quote! {}
+    };
+
     let provider_body = quote::quote! {{
         type Impl = #comp_name;
-        fn get(&mut self) -> waiter_di::Wrc<Self::Impl> {
-            waiter_di::Provider::<#comp_name>::get(self)
+        fn try_get(&mut self) -> Result<waiter_di::Wrc<Self::Impl>, waiter_di::ResolveError> {
+            #ensure_not_overridden
+            waiter_di::Provider::<#comp_name>::try_get(self)
+        }
+        fn try_create(&mut self) -> Result<Self::Impl, waiter_di::ResolveError> {
+            #ensure_create_not_overridden
+            waiter_di::Provider::<#comp_name>::try_create(self)
+        }
+        #get_ref_code
+    }};
+
+    let upcast_body = quote::quote! {{
+        fn __waiter_upcast(component: waiter_di::Wrc<Self::Impl>) -> waiter_di::Wrc<dyn #interface> {
+            component
+        }
+        fn __waiter_upcast_boxed(component: Box<Self::Impl>) -> Box<dyn #interface> {
+            component
+        }
+    }};
+
+    let async_provider_body = quote::quote! {{
+        fn try_get_async(
+            &mut self
+        ) -> waiter_di::BoxFuture<'_, Result<waiter_di::Wrc<Self::Impl>, waiter_di::ResolveError>> {
+            Box::pin(async move {
+                #ensure_not_overridden
+                waiter_di::AsyncProvider::<#comp_name>::try_get_async(self).await
+            })
         }
-        fn create(&mut self) -> Self::Impl {
-            waiter_di::Provider::<#comp_name>::create(self)
+        fn try_create_async(
+            &mut self
+        ) -> waiter_di::BoxFuture<'_, Result<Self::Impl, waiter_di::ResolveError>> {
+            Box::pin(async move {
+                #ensure_create_not_overridden
+                waiter_di::AsyncProvider::<#comp_name>::try_create_async(self).await
+            })
         }
     }};
+    let async_provider = async_provider.filter(|_| cfg!(feature = "async"));
 
     let profiles = provides.profiles;
     let result = if profiles.is_empty() {
+        let upcast_code = upcast.map(|upcast| quote::quote! {
+            impl<P> #upcast for waiter_di::Container<P> #upcast_body
+        });
+        let async_provider_code = async_provider.map(|async_provider| quote::quote! {
+            impl<P> #async_provider for waiter_di::Container<P> #async_provider_body
+        });
         quote::quote! {
-            impl<P> waiter_di::Provider<dyn #interface> for waiter_di::Container<P> #provider_body
+            impl<P> #provider for waiter_di::Container<P> #provider_body
+            #upcast_code
+            #async_provider_code
+            #bindings_code
         }
     } else {
+        let upcast_code = upcast.map(|upcast| quote::quote! {
+            #(impl #upcast for waiter_di::Container<#profiles> #upcast_body)*
+        });
+        let async_provider_code = async_provider.map(|async_provider| quote::quote! {
+            #(impl #async_provider for waiter_di::Container<#profiles> #async_provider_body)*
+        });
         quote::quote! {
-            #(impl waiter_di::Provider<dyn #interface> for waiter_di::Container<#profiles> #provider_body)*
+            #(impl #provider for waiter_di::Container<#profiles> #provider_body)*
+            #upcast_code
+            #async_provider_code
+            #bindings_code
         }
     };
 
     TokenStream::from(result)
-}
\ No newline at end of file
+}
+
+/// Implementations of traits which `Provider` is generated in the compiled crate, by trait and
+/// profiles. `Provider<dyn Trait>` can be implemented once, so other implementations without
+/// `order` are only registered to be selected at runtime. The same implementation is found again
+/// if it's expanded twice, e.g. by an IDE
+static STATIC_PROVIDERS: Mutex<Vec<(String, String)>> = Mutex::new(Vec::new());
+
+fn is_static_provider(interface: &Path, profiles: &[Path], comp_name: &Ident) -> bool {
+    let key = format!(
+        "{}: {} for {}",
+        std::env::var("CARGO_CRATE_NAME").unwrap_or_default(),
+        interface.to_token_stream(),
+        quote::quote! { #(#profiles),* },
+    );
+    let component = comp_name.to_string();
+
+    let mut providers = STATIC_PROVIDERS.lock().unwrap_or_else(PoisonError::into_inner);
+    match providers.iter().find(|(existing, _)| *existing == key) {
+        Some((_, provider)) => *provider == component,
+        None => {
+            providers.push((key, component));
+            true
+        }
+    }
+}
+
+fn generate_interface_bindings(provides: &ProvidesAttr, interface: &Path, comp_name: &Ident) -> TokenStream2 {
+    let order = provides.order
+        .map(|order| quote::quote! { Some(#order) })
+        .unwrap_or_else(|| quote::quote! { None });
+    let name = provides.name.as_ref()
+        .map(|name| quote::quote! { Some(#name) })
+        .unwrap_or_else(|| quote::quote! { None });
+    let conditions = generate_conditions_code(provides);
+
+    // Without profiles the component is resolved in the erased container, so its dependencies
+    // are selected by the profile of the container that requested it
+    let profiles: Vec<(Option<TokenStream2>, TokenStream2)> = if provides.profiles.is_empty() {
+        vec!((None, quote::quote! { None }))
+    } else {
+        provides.profiles.iter()
+            .map(|profile| (
+                Some(profile.to_token_stream()),
+                quote::quote! { Some(std::any::type_name::<#profile>) }
+            ))
+            .collect()
+    };
+
+    let bindings = profiles.into_iter().map(|(profile, profile_filter)| {
+        let (get_code, create_code) = match profile {
+            Some(profile) => (
+                quote::quote! {
+                    container.__waiter_with_profile(|container: &mut waiter_di::Container<#profile>| {
+                        waiter_di::Provider::<#comp_name>::try_get(container)
+                    })
+                },
+                quote::quote! {
+                    container.__waiter_with_profile(|container: &mut waiter_di::Container<#profile>| {
+                        waiter_di::Provider::<#comp_name>::try_create(container).map(Box::new)
+                    })
+                },
+            ),
+            None => (
+                quote::quote! {{
+                    use waiter_di::{__WaiterRuntimeResolve, __WaiterStaticResolve};
+                    (&waiter_di::__WaiterResolver::<#comp_name>::new()).__waiter_try_get(container)
+                }},
+                quote::quote! {{
+                    use waiter_di::{__WaiterRuntimeResolve, __WaiterStaticResolve};
+                    (&waiter_di::__WaiterResolver::<#comp_name>::new()).__waiter_try_create(container)
+                }},
+            ),
+        };
+
+        quote::quote! {
+            const _: () = {
+                fn get(
+                    container: &mut waiter_di::Container<()>
+                ) -> Result<Box<dyn std::any::Any>, waiter_di::ResolveError> {
+                    #get_code
+                        .map(|component| Box::new(component as waiter_di::Wrc<dyn #interface>) as Box<dyn std::any::Any>)
+                }
+                fn create(
+                    container: &mut waiter_di::Container<()>
+                ) -> Result<Box<dyn std::any::Any>, waiter_di::ResolveError> {
+                    #create_code
+                        .map(|component| Box::new(component as Box<dyn #interface>) as Box<dyn std::any::Any>)
+                }
+
+                waiter_di::inventory::submit! {
+                    waiter_di::InterfaceBinding {
+                        interface: std::any::TypeId::of::<dyn #interface>,
+                        interface_name: std::any::type_name::<dyn #interface>,
+                        component: std::any::type_name::<#comp_name>,
+                        profile: #profile_filter,
+                        order: #order,
+                        name: #name,
+                        conditions: &[#(#conditions),*],
+                        get,
+                        create,
+                    }
+                }
+            };
+        }
+    });
+
+    quote::quote! { #(#bindings)* }
+}
+
+fn generate_conditions_code(provides: &ProvidesAttr) -> Vec<TokenStream2> {
+    let mut conditions = vec!();
+    if let Some(profile_expr) = &provides.profile_expr {
+        let profile_expr = profile_expr.to_tokens();
+        conditions.push(quote::quote! { waiter_di::Condition::Profile(#profile_expr) });
+    }
+    for (prop, value) in &provides.when_props {
+        conditions.push(match value {
+            Some(value) => quote::quote! { waiter_di::Condition::PropertyEquals(#prop, #value) },
+            None => quote::quote! { waiter_di::Condition::Property(#prop) }
+        });
+    }
+    for type_ in &provides.when_missing {
+        conditions.push(quote::quote! { waiter_di::Condition::Missing(std::any::TypeId::of::<#type_>) });
+    }
+    conditions
+}
@@ -0,0 +1,57 @@
+extern crate waiter_di;
+
+use waiter_di::*;
+
+// Parent and child refer to each other, the back-reference is weak,
+// so the parent is dropped with the container even if the child is still referenced
+
+trait Parent: ContainerSafe {
+    fn name(&self) -> &str;
+}
+
+#[component]
+struct Tree {
+    leaf: Wrc<Leaf>,
+}
+
+#[provides]
+impl Parent for Tree {
+    fn name(&self) -> &str {
+        "tree"
+    }
+}
+
+impl Drop for Tree {
+    fn drop(&mut self) {
+        println!("Tree dropped");
+    }
+}
+
+#[component]
+struct Leaf {
+    parent: WeakRef<dyn Parent>,
+}
+
+impl Leaf {
+    fn describe(&self) {
+        println!("Leaf of {}", self.parent.get().name());
+    }
+}
+
+impl Drop for Leaf {
+    fn drop(&mut self) {
+        println!("Leaf dropped");
+    }
+}
+
+fn main() {
+    let mut container = Container::<profiles::Default>::new();
+
+    let leaf = Provider::<Tree>::get(&mut container).leaf.clone();
+    leaf.describe();
+
+    drop(container);
+
+    // The leaf is still referenced here, but its parent is already dropped
+    println!("Parent is alive: {}", leaf.parent.upgrade().is_some());
+}
@@ -0,0 +1,201 @@
+use config::{Config, Value};
+
+use crate::ResolveError;
+
+/// Reads properties from command line arguments:
+/// `--key value`, `--key=value`, `-Dkey=value` and `--flag`, which is `true`.
+/// Repeated keys are collected into an array, arguments after `--` aren't read.
+/// A key passed once is a single value, e.g. `--tag a` is the string `a`, not an array of it
+#[derive(Debug, Clone, Default)]
+pub struct ArgsParser {
+    prefix: Option<String>,
+    skip_malformed: bool,
+}
+
+impl ArgsParser {
+    pub fn new() -> Self {
+        Self { prefix: None, skip_malformed: false }
+    }
+
+    /// Reads only keys starting with the prefix, e.g. `--app.db.url` for `app.` prefix.
+    /// The prefix is removed from keys, other arguments are left to the application
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.prefix = Some(prefix.to_string());
+        self
+    }
+
+    /// Skips arguments that can't be read as properties instead of failing, e.g. `-Dflag`
+    /// of another tool that reads arguments of the same process
+    pub fn skip_malformed(mut self) -> Self {
+        self.skip_malformed = true;
+        self
+    }
+
+    pub fn parse(&self, args: impl IntoIterator<Item = String>) -> Result<Config, ResolveError> {
+        let mut config = Config::builder();
+        for (key, mut values) in self.properties(args)? {
+            let value = if values.len() == 1 {
+                values.remove(0)
+            } else {
+                Value::from(values)
+            };
+            config = match config.clone().set_override(key.as_str(), value) {
+                Ok(config) => config,
+                Err(_) if self.skip_malformed => config,
+                Err(error) => return Err(invalid_argument(&key, &error.to_string())),
+            };
+        }
+        config.build().map_err(ResolveError::config_load)
+    }
+
+    fn properties(
+        &self,
+        args: impl IntoIterator<Item = String>,
+    ) -> Result<Vec<(String, Vec<Value>)>, ResolveError> {
+        let mut properties: Vec<(String, Vec<Value>)> = vec!();
+        let mut args = args.into_iter().peekable();
+        while let Some(arg) = args.next() {
+            if arg == "--" {
+                break;
+            }
+
+            let (key, value) = if let Some(property) = arg.strip_prefix("-D") {
+                match property.split_once('=') {
+                    Some((key, value)) => (key, Value::from(value)),
+                    None if self.is_skipped(property) || self.skip_malformed => continue,
+                    None => return Err(invalid_argument(&arg, "expected -Dkey=value")),
+                }
+            } else if let Some(property) = arg.strip_prefix("--") {
+                match property.split_once('=') {
+                    Some((key, value)) => (key, Value::from(value)),
+                    None if self.is_skipped(property) => continue,
+                    // Negative numbers are values, e.g. `--offset -5`
+                    None => match args.next_if(|value| !is_key(value)) {
+                        Some(value) => (property, Value::from(value)),
+                        None => (property, Value::from(true)),
+                    },
+                }
+            } else {
+                continue;
+            };
+
+            if self.is_skipped(key) {
+                continue;
+            }
+            let key = self.prefix.as_ref()
+                .map_or(key, |prefix| &key[prefix.len()..]);
+            if key.is_empty() {
+                if self.skip_malformed {
+                    continue;
+                }
+                return Err(invalid_argument(&arg, "property name is empty"));
+            }
+
+            match properties.iter_mut().find(|(existing, _)| existing == key) {
+                Some((_, values)) => values.push(value),
+                None => properties.push((key.to_string(), vec!(value))),
+            }
+        }
+        Ok(properties)
+    }
+
+    fn is_skipped(&self, key: &str) -> bool {
+        self.prefix.as_ref().is_some_and(|prefix| !key.starts_with(prefix.as_str()))
+    }
+}
+
+fn is_key(arg: &str) -> bool {
+    arg.starts_with("--") || arg.starts_with("-D")
+}
+
+fn invalid_argument(arg: &str, reason: &str) -> ResolveError {
+    ResolveError::config_load(format!("invalid argument '{}', {}", arg, reason))
+}
+
+/// Properties from arguments of the process, panics if they are malformed
+#[deprecated(note = "use try_parse_args, which returns malformed arguments as an error")]
+pub fn parse_args() -> Config {
+    try_parse_args().unwrap_or_else(|error| panic!("{}", error))
+}
+
+/// Properties from arguments of the process, the first one is the path of the binary
+pub fn try_parse_args() -> Result<Config, ResolveError> {
+    ArgsParser::new().parse(std::env::args().skip(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use config::Config;
+
+    use crate::{ArgsParser, ResolveError};
+
+    fn parse(parser: ArgsParser, args: &[&str]) -> Result<Config, ResolveError> {
+        parser.parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn key_value_forms() {
+        let config = parse(ArgsParser::new(), &[
+            "--port=8080", "--db.url", "postgres://", "positional", "-Dname=app", "--offset", "-5",
+            "--verbose", "--", "--ignored=1",
+        ]).unwrap();
+
+        assert_eq!(8080, config.get_int("port").unwrap());
+        assert_eq!("postgres://", config.get_string("db.url").unwrap());
+        assert_eq!("app", config.get_string("name").unwrap());
+        assert_eq!(-5, config.get_int("offset").unwrap());
+        assert!(config.get_bool("verbose").unwrap());
+        assert!(config.get_string("ignored").is_err());
+    }
+
+    #[test]
+    fn repeated_keys_are_arrays() {
+        let config = parse(ArgsParser::new(), &["--tag", "a", "-Dtag=b", "--tag=c"]).unwrap();
+
+        assert_eq!(vec!("a", "b", "c"), config.get::<Vec<String>>("tag").unwrap());
+    }
+
+    #[test]
+    fn single_key_is_not_array() {
+        let config = parse(ArgsParser::new(), &["--tag", "a"]).unwrap();
+
+        assert_eq!("a", config.get_string("tag").unwrap());
+        assert!(config.get::<Vec<String>>("tag").is_err());
+    }
+
+    #[test]
+    fn only_prefixed_keys() {
+        let config = parse(ArgsParser::new().prefix("app."), &[
+            "--app.db.url=postgres://", "-Dapp.port=1", "--threads", "4", "-Dfile.encoding",
+            "--app.verbose",
+        ]).unwrap();
+
+        assert_eq!("postgres://", config.get_string("db.url").unwrap());
+        assert_eq!(1, config.get_int("port").unwrap());
+        assert!(config.get_bool("verbose").unwrap());
+        assert!(config.get_int("threads").is_err());
+    }
+
+    #[test]
+    fn malformed_arguments_are_skipped() {
+        let config = parse(ArgsParser::new().skip_malformed(), &[
+            "-Dfoo", "--=value", "-D=1", "-Dname=app", "--port", "8080",
+        ]).unwrap();
+
+        assert_eq!("app", config.get_string("name").unwrap());
+        assert_eq!(8080, config.get_int("port").unwrap());
+    }
+
+    #[test]
+    fn malformed_arguments() {
+        let errors = vec!(
+            parse(ArgsParser::new(), &["-Dname"]),
+            parse(ArgsParser::new(), &["--=value"]),
+            parse(ArgsParser::new().prefix("app."), &["--app.", "1"]),
+        );
+
+        for error in errors {
+            assert!(matches!(error, Err(ResolveError::ConfigLoad { .. })));
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use std::future::{ready, Future};
+use std::pin::Pin;
+
+use crate::{__WaiterResolver, __WaiterUpcast, Container, ContainerSafe, Provider, ResolveError, Wrc};
+
+/// Futures are `Send`, so components can be resolved in spawned tasks
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Resolves components created by `async fn` factories or initialized by async `#[post_construct]`
+/// hooks. Dependencies are awaited one by one before the component that needs them
+pub trait AsyncProvider<T: ?Sized>: Provider<T> {
+    fn try_get_async(&mut self) -> BoxFuture<'_, Result<Wrc<Self::Impl>, ResolveError>>;
+    fn try_create_async(&mut self) -> BoxFuture<'_, Result<Self::Impl, ResolveError>>;
+
+    fn get_async(&mut self) -> BoxFuture<'_, Wrc<Self::Impl>> where Self::Impl: 'static {
+        let component = Self::try_get_async(self);
+        Box::pin(async move { component.await.unwrap_or_else(|error| panic!("{}", error)) })
+    }
+    fn create_async(&mut self) -> BoxFuture<'_, Self::Impl> where Self::Impl: 'static {
+        let component = Self::try_create_async(self);
+        Box::pin(async move { component.await.unwrap_or_else(|error| panic!("{}", error)) })
+    }
+}
+
+/// Used by macros when the dependency has an async provider for all profiles
+#[doc(hidden)]
+pub trait __WaiterAsyncStaticResolve<T: ?Sized> {
+    fn __waiter_try_get_async<'a, P>(
+        &self,
+        container: &'a mut Container<P>,
+    ) -> BoxFuture<'a, Result<Wrc<T>, ResolveError>>
+        where Container<P>: AsyncProvider<T> + __WaiterUpcast<T>;
+    fn __waiter_try_create_async<'a, P>(
+        &self,
+        container: &'a mut Container<P>,
+    ) -> BoxFuture<'a, Result<Box<T>, ResolveError>>
+        where Container<P>: AsyncProvider<T> + __WaiterUpcast<T>;
+}
+
+impl<T: ?Sized + ContainerSafe + 'static> __WaiterAsyncStaticResolve<T> for &__WaiterResolver<T>
+    where Container<()>: AsyncProvider<T> + __WaiterUpcast<T>
+{
+    fn __waiter_try_get_async<'a, P>(
+        &self,
+        container: &'a mut Container<P>,
+    ) -> BoxFuture<'a, Result<Wrc<T>, ResolveError>>
+        where Container<P>: AsyncProvider<T> + __WaiterUpcast<T>
+    {
+        Box::pin(async move {
+            container.__waiter_ensure_open()?;
+            if let Some(component) = container.overridden::<T>() {
+                return Ok(component);
+            }
+            AsyncProvider::<T>::try_get_async(container).await
+                .map(<Container<P> as __WaiterUpcast<T>>::__waiter_upcast)
+        })
+    }
+
+    fn __waiter_try_create_async<'a, P>(
+        &self,
+        container: &'a mut Container<P>,
+    ) -> BoxFuture<'a, Result<Box<T>, ResolveError>>
+        where Container<P>: AsyncProvider<T> + __WaiterUpcast<T>
+    {
+        Box::pin(async move {
+            container.__waiter_ensure_open()?;
+            if let Some(component) = container.overridden_create::<T>() {
+                return Ok(component);
+            }
+            AsyncProvider::<T>::try_create_async(container).await
+                .map(|component| <Container<P> as __WaiterUpcast<T>>::__waiter_upcast_boxed(Box::new(component)))
+        })
+    }
+}
+
+/// Used by macros when the dependency has only a synchronous provider, e.g. a generic component
+#[doc(hidden)]
+pub trait __WaiterAsyncFallbackResolve<T: ?Sized> {
+    fn __waiter_try_get_async<'a, P>(
+        &self,
+        container: &'a mut Container<P>,
+    ) -> BoxFuture<'a, Result<Wrc<T>, ResolveError>>
+        where Container<P>: __WaiterUpcast<T>;
+    fn __waiter_try_create_async<'a, P>(
+        &self,
+        container: &'a mut Container<P>,
+    ) -> BoxFuture<'a, Result<Box<T>, ResolveError>>
+        where Container<P>: __WaiterUpcast<T>;
+}
+
+impl<T: ?Sized + ContainerSafe + 'static> __WaiterAsyncFallbackResolve<T> for &&__WaiterResolver<T>
+    where Container<()>: __WaiterUpcast<T>
+{
+    fn __waiter_try_get_async<'a, P>(
+        &self,
+        container: &'a mut Container<P>,
+    ) -> BoxFuture<'a, Result<Wrc<T>, ResolveError>>
+        where Container<P>: __WaiterUpcast<T>
+    {
+        use crate::__WaiterStaticResolve;
+        Box::pin(ready(__WaiterResolver::<T>::new().__waiter_try_get(container)))
+    }
+
+    fn __waiter_try_create_async<'a, P>(
+        &self,
+        container: &'a mut Container<P>,
+    ) -> BoxFuture<'a, Result<Box<T>, ResolveError>>
+        where Container<P>: __WaiterUpcast<T>
+    {
+        use crate::__WaiterStaticResolve;
+        Box::pin(ready(__WaiterResolver::<T>::new().__waiter_try_create(container)))
+    }
+}
+
+/// Used by macros to select an implementation at runtime, which conditions are met
+#[doc(hidden)]
+pub trait __WaiterAsyncRuntimeResolve<T: ?Sized> {
+    fn __waiter_try_get_async<'a, P>(
+        &self,
+        container: &'a mut Container<P>,
+    ) -> BoxFuture<'a, Result<Wrc<T>, ResolveError>>;
+    fn __waiter_try_create_async<'a, P>(
+        &self,
+        container: &'a mut Container<P>,
+    ) -> BoxFuture<'a, Result<Box<T>, ResolveError>>;
+}
+
+impl<T: ?Sized + ContainerSafe + 'static> __WaiterAsyncRuntimeResolve<T> for __WaiterResolver<T> {
+    fn __waiter_try_get_async<'a, P>(
+        &self,
+        container: &'a mut Container<P>,
+    ) -> BoxFuture<'a, Result<Wrc<T>, ResolveError>> {
+        Box::pin(ready(container.try_get_selected::<T>()))
+    }
+
+    fn __waiter_try_create_async<'a, P>(
+        &self,
+        container: &'a mut Container<P>,
+    ) -> BoxFuture<'a, Result<Box<T>, ResolveError>> {
+        Box::pin(ready(container.try_create_selected::<T>()))
+    }
+}
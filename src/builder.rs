@@ -0,0 +1,233 @@
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+use config::{Config, ConfigError, File, Map, Source, Value};
+
+use crate::{profile_name, ArgsParser, Container, EnvVar, ResolveError};
+use crate::env::EnvSource;
+
+const DEFAULT_ENV_SEPARATOR: &str = "__";
+
+/// Source of properties, sources added later override properties of the previous ones
+#[derive(Debug)]
+pub enum ConfigSource {
+    /// `default` and active profile files from the config directory, e.g. `config/default.toml`
+    /// and `config/dev.toml`
+    ProfileFiles,
+    /// File, its format is detected by the extension
+    File { path: PathBuf, required: bool },
+    /// Environment variables, see `ContainerBuilder::env_prefix`
+    Environment,
+    /// Command line arguments, see `ArgsParser`
+    Args,
+    /// Properties set in code, keys can be nested as `db.url`
+    Map(Map<String, Value>),
+    Custom(Box<dyn Source + Send + Sync>),
+}
+
+impl ConfigSource {
+    pub fn file(path: impl AsRef<Path>) -> Self {
+        ConfigSource::File { path: path.as_ref().to_path_buf(), required: true }
+    }
+
+    pub fn optional_file(path: impl AsRef<Path>) -> Self {
+        ConfigSource::File { path: path.as_ref().to_path_buf(), required: false }
+    }
+
+    pub fn map<K: Into<String>, V: Into<Value>>(properties: impl IntoIterator<Item = (K, V)>) -> Self {
+        ConfigSource::Map(properties.into_iter()
+            .map(|(key, value)| (key.into(), value.into()))
+            .collect())
+    }
+
+    pub fn custom(source: impl Source + Send + Sync + 'static) -> Self {
+        ConfigSource::Custom(Box::new(source))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct MapSource(Map<String, Value>);
+
+impl Source for MapSource {
+    fn clone_into_box(&self) -> Box<dyn Source + Send + Sync> {
+        Box::new(self.clone())
+    }
+
+    fn collect(&self) -> Result<Map<String, Value>, ConfigError> {
+        Ok(self.0.clone())
+    }
+}
+
+/// Configures where the container reads properties from. `Container::new()` is the same as
+/// `Container::builder().build()`
+pub struct ContainerBuilder<P> {
+    profile: PhantomData<P>,
+    profiles: Vec<String>,
+    loader: ConfigLoader,
+}
+
+/// Sources of the container config, they are read again by `Container::reload_config`
+#[derive(Debug, Default)]
+pub struct ConfigLoader {
+    profiles: Vec<String>,
+    config_dir: PathBuf,
+    sources: Vec<ConfigSource>,
+    env_prefix: Option<String>,
+    env_separator: Option<String>,
+    args_prefix: Option<String>,
+}
+
+impl<P> ContainerBuilder<P> {
+    pub fn new() -> Self {
+        Self {
+            profile: PhantomData::<P>,
+            profiles: vec!(),
+            loader: ConfigLoader {
+                profiles: vec!(),
+                config_dir: PathBuf::from("config"),
+                sources: vec!(ConfigSource::ProfileFiles, ConfigSource::Environment, ConfigSource::Args),
+                env_prefix: None,
+                env_separator: None,
+                args_prefix: None,
+            },
+        }
+    }
+
+    /// Activates additional profiles, like `Container::with_profiles`
+    pub fn profiles(mut self, profiles: &[&str]) -> Self {
+        self.profiles = profiles.iter().map(|profile| profile.to_string()).collect();
+        self
+    }
+
+    /// Directory of profile files, `config` in the working directory by default
+    pub fn config_dir(mut self, dir: impl AsRef<Path>) -> Self {
+        self.loader.config_dir = dir.as_ref().to_path_buf();
+        self
+    }
+
+    /// Adds the source after the others, so it overrides their properties
+    pub fn add_source(mut self, source: ConfigSource) -> Self {
+        self.loader.sources.push(source);
+        self
+    }
+
+    /// Replaces all sources, e.g. to change their order
+    pub fn sources(mut self, sources: Vec<ConfigSource>) -> Self {
+        self.loader.sources = sources;
+        self
+    }
+
+    /// Reads only variables starting with the prefix, e.g. `APP_PORT` for `APP` prefix.
+    /// Nested keys are separated by `__` unless another separator is set
+    pub fn env_prefix(mut self, prefix: &str) -> Self {
+        self.loader.env_prefix = Some(prefix.to_string());
+        self
+    }
+
+    /// Separator of nested keys in variable names, e.g. `DB__URL` for `db.url` with `__`
+    pub fn env_separator(mut self, separator: &str) -> Self {
+        self.loader.env_separator = Some(separator.to_string());
+        self
+    }
+
+    /// Reads only arguments with the prefix, e.g. `--app.port=8080` as `port` for `app.` prefix
+    pub fn args_prefix(mut self, prefix: &str) -> Self {
+        self.loader.args_prefix = Some(prefix.to_string());
+        self
+    }
+
+    /// Properties aren't read from command line arguments
+    pub fn without_args(mut self) -> Self {
+        self.loader.sources.retain(|source| !matches!(source, ConfigSource::Args));
+        self
+    }
+
+    /// Properties aren't read from environment variables
+    pub fn without_env(mut self) -> Self {
+        self.loader.sources.retain(|source| !matches!(source, ConfigSource::Environment));
+        self
+    }
+
+    pub fn build(self) -> Container<P> {
+        self.try_build().unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    pub fn try_build(mut self) -> Result<Container<P>, ResolveError> {
+        let mut active_profiles = vec!(profile_name::<P>());
+        for profile in &self.profiles {
+            if !active_profiles.contains(profile) {
+                active_profiles.push(profile.clone());
+            }
+        }
+
+        self.loader.profiles = active_profiles.clone();
+        let (config, env_vars) = self.loader.load()?;
+        Ok(Container::from_config(active_profiles, config, env_vars, self.loader))
+    }
+}
+
+impl ConfigLoader {
+    pub fn load(&self) -> Result<(Config, Vec<EnvVar>), ResolveError> {
+        let mut env_vars = vec!();
+        let mut config = Config::builder();
+        for source in &self.sources {
+            config = match source {
+                ConfigSource::ProfileFiles => {
+                    for path in self.profile_files() {
+                        config = config.add_source(File::with_name(&path.to_string_lossy()).required(false));
+                    }
+                    config
+                }
+                ConfigSource::File { path, required } =>
+                    config.add_source(File::with_name(&path.to_string_lossy()).required(*required)),
+                ConfigSource::Environment => {
+                    let separator = self.env_separator.as_deref()
+                        .or(self.env_prefix.as_ref().map(|_| DEFAULT_ENV_SEPARATOR));
+                    let environment = EnvSource::new(self.env_prefix.as_deref(), separator);
+                    env_vars.extend(environment.vars());
+                    config.add_source(environment)
+                }
+                ConfigSource::Args => {
+                    // Without the prefix all arguments are read, including ones of other tools
+                    let parser = match &self.args_prefix {
+                        Some(prefix) => ArgsParser::new().prefix(prefix),
+                        None => ArgsParser::new().skip_malformed(),
+                    };
+                    config.add_source(parser.parse(std::env::args().skip(1))?)
+                }
+                ConfigSource::Map(properties) => config.add_source(MapSource(properties.clone())),
+                ConfigSource::Custom(source) => config.add_source(vec!(source.clone_into_box())),
+            };
+        }
+        let config = config.build().map_err(ResolveError::config_load)?;
+        Ok((config, env_vars))
+    }
+
+    /// Files read by the loader, profile files and files without extension can have any
+    /// of supported extensions
+    pub fn files(&self) -> Vec<PathBuf> {
+        self.sources.iter()
+            .flat_map(|source| match source {
+                ConfigSource::ProfileFiles => self.profile_files(),
+                ConfigSource::File { path, .. } => vec!(path.clone()),
+                _ => vec!(),
+            })
+            .collect()
+    }
+
+    fn profile_files(&self) -> Vec<PathBuf> {
+        let profiles = self.profiles.iter()
+            .map(String::as_str)
+            .filter(|profile| *profile != "default");
+        std::iter::once("default")
+            .chain(profiles)
+            .map(|profile| self.config_dir.join(profile))
+            .collect()
+    }
+}
+
+impl<P> Default for ContainerBuilder<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,36 @@
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+
+use crate::Container;
+
+/// Container of a request or a unit of work created by `Container::child()`. It uses config and
+/// singletons of the parent container, while `#[component(scope = "request")]` components are
+/// cached only until the child container is dropped. Several children can exist at the same time,
+/// components of other custom scopes are cached by each child separately
+pub struct ChildContainer<'p, P> {
+    container: Container<P>,
+    parent: PhantomData<&'p Container<P>>,
+}
+
+impl<P> Container<P> {
+    pub fn child(&self) -> ChildContainer<'_, P> {
+        let mut container = self.share::<P>(self.components.clone());
+        container.is_child = true;
+
+        ChildContainer { container, parent: PhantomData }
+    }
+}
+
+impl<P> Deref for ChildContainer<'_, P> {
+    type Target = Container<P>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.container
+    }
+}
+
+impl<P> DerefMut for ChildContainer<'_, P> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.container
+    }
+}
@@ -0,0 +1,95 @@
+use std::any::TypeId;
+
+use config::{ConfigError, Value, ValueKind};
+
+use crate::{Container, ResolveError};
+use crate::registry::has_active_binding;
+
+/// Condition of `#[provides(...)]` evaluated when component is resolved
+pub enum Condition {
+    /// Profile expression like `dev & !mock-db`, supports `&`, `|`, `!` and parentheses
+    Profile(ProfileExpr),
+    /// Property exists and isn't `false`, numbers, tables and arrays are always present
+    Property(&'static str),
+    PropertyEquals(&'static str, &'static str),
+    /// There is no other active implementation of the type
+    Missing(fn() -> TypeId),
+}
+
+impl Condition {
+    pub fn check<P>(&self, container: &Container<P>) -> Result<bool, ResolveError> {
+        match self {
+            Condition::Profile(expr) => Ok(expr.matches(container.active_profiles())),
+            Condition::Property(prop) => match container.config.get::<Value>(prop) {
+                Ok(value) => Ok(match value.kind {
+                    ValueKind::Nil => false,
+                    ValueKind::Boolean(value) => value,
+                    ValueKind::String(value) => value != "false",
+                    // Numbers, tables and arrays are present and can't be `false`
+                    _ => true
+                }),
+                Err(ConfigError::NotFound(_)) => Ok(false),
+                Err(error) => Err(ResolveError::invalid_condition(prop, error))
+            },
+            Condition::PropertyEquals(prop, expected) => match container.config.get_string(prop) {
+                Ok(value) => Ok(value == *expected),
+                Err(ConfigError::NotFound(_)) => Ok(false),
+                Err(error) => Err(ResolveError::invalid_condition(prop, error))
+            },
+            Condition::Missing(type_id) => has_active_binding(type_id(), container)
+                .map(|has_binding| !has_binding),
+        }
+    }
+}
+
+/// Profile expression like `dev & !mock-db`, it's parsed by `#[provides(profile = "...")]`,
+/// so malformed expressions don't compile
+#[derive(Debug, PartialEq)]
+pub enum ProfileExpr {
+    Profile(&'static str),
+    Not(&'static ProfileExpr),
+    And(&'static ProfileExpr, &'static ProfileExpr),
+    Or(&'static ProfileExpr, &'static ProfileExpr),
+}
+
+impl ProfileExpr {
+    pub fn matches(&self, active_profiles: &[String]) -> bool {
+        match self {
+            ProfileExpr::Profile(profile) => active_profiles.iter().any(|active| active == profile),
+            ProfileExpr::Not(expr) => !expr.matches(active_profiles),
+            ProfileExpr::And(left, right) =>
+                left.matches(active_profiles) && right.matches(active_profiles),
+            ProfileExpr::Or(left, right) =>
+                left.matches(active_profiles) || right.matches(active_profiles),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::condition::ProfileExpr;
+
+    fn matches(expr: &ProfileExpr, active_profiles: &[&str]) -> bool {
+        let active_profiles: Vec<String> = active_profiles.iter().map(|p| p.to_string()).collect();
+        expr.matches(&active_profiles)
+    }
+
+    #[test]
+    fn evaluate_expressions() {
+        const DEV_WITHOUT_MOCK: ProfileExpr = ProfileExpr::And(
+            &ProfileExpr::Profile("dev"),
+            &ProfileExpr::Not(&ProfileExpr::Profile("mock-db")),
+        );
+        const PROD_IN_REGION: ProfileExpr = ProfileExpr::And(
+            &ProfileExpr::Profile("prod"),
+            &ProfileExpr::Or(&ProfileExpr::Profile("eu-region"), &ProfileExpr::Profile("us-region")),
+        );
+
+        assert!(matches(&ProfileExpr::Profile("dev"), &["dev"]));
+        assert!(matches(&DEV_WITHOUT_MOCK, &["dev", "kafka"]));
+        assert!(!matches(&DEV_WITHOUT_MOCK, &["dev", "mock-db"]));
+        assert!(matches(&PROD_IN_REGION, &["prod", "eu-region"]));
+        assert!(!matches(&PROD_IN_REGION, &["prod"]));
+        assert!(!matches(&ProfileExpr::Not(&ProfileExpr::Not(&ProfileExpr::Profile("a"))), &[]));
+    }
+}
@@ -3,12 +3,19 @@ use std::collections::HashMap;
 use std::env;
 use std::env::args;
 use std::marker::PhantomData;
+use std::sync::Arc;
+#[cfg(feature = "async")]
+use std::sync::PoisonError;
 
-use config::{Config, Environment, File};
+use config::{Config, File};
 use lazy_static::lazy_static;
 use regex::Regex;
 
-use crate::{RcAny, Wrc};
+use crate::{ConfigLoader, ContainerBuilder, EnvVar, LiveConfig, RcAny, ResolveError, Singleton, Wrc};
+use crate::lifecycle::PendingSingleton;
+#[cfg(feature = "async")]
+use crate::lifecycle::PostponedHook;
+use crate::scope::ErasedScope;
 
 pub mod profiles {
     pub struct Default;
@@ -18,34 +25,241 @@ pub mod profiles {
     pub struct Test;
 }
 
-pub trait Component {
-    fn __waiter_create<P>(container: &mut Container<P>) -> Self;
-    fn __waiter_inject_deferred<P>(container: &mut Container<P>, component: &Self);
+pub trait Component: Sized {
+    fn __waiter_create<P>(container: &mut Container<P>) -> Result<Self, ResolveError>;
+    fn __waiter_inject_deferred<P>(
+        container: &mut Container<P>,
+        component: &Self,
+    ) -> Result<(), ResolveError>;
 }
 
+/// Providers generated by macros implement `try_get` and `try_create`, hand-written ones
+/// can implement either them or `get` and `create`
 pub trait Provider<T: ?Sized> {
     type Impl;
-    fn get(&mut self) -> Wrc<Self::Impl>;
-    fn create(&mut self) -> Self::Impl;
+    fn try_get(&mut self) -> Result<Wrc<Self::Impl>, ResolveError> {
+        Ok(Self::get(self))
+    }
+    fn try_create(&mut self) -> Result<Self::Impl, ResolveError> {
+        Ok(Self::create(self))
+    }
+
+    fn get(&mut self) -> Wrc<Self::Impl> {
+        Self::try_get(self).unwrap_or_else(|error| panic!("{}", error))
+    }
+    fn create(&mut self) -> Self::Impl {
+        Self::try_create(self).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Reference to a singleton, it's implemented by macros only for singletons, since other
+    /// components aren't kept by the container
+    fn try_get_ref(&mut self) -> Result<&Self::Impl, ResolveError> {
+        Err(ResolveError::not_singleton(type_name::<Self::Impl>()))
+    }
 
     fn get_ref(&mut self) -> &Self::Impl {
-        // Value under RC is still stored in container, so it can be safely returned as a reference
-        // that has the same life as container reference
-        unsafe {
-            Wrc::as_ptr(&Self::get(self))
-                .as_ref()
-                .unwrap()
-        }
+        Self::try_get_ref(self).unwrap_or_else(|error| panic!("{}", error))
     }
     fn create_boxed(&mut self) -> Box<Self::Impl> {
         Box::new(Self::create(self))
     }
 }
 
+/// Converts component to the provided type, e.g. `Wrc<Impl>` to `Wrc<dyn Interface>`. It's
+/// implemented for every provider of a concrete type and by macros for providers of traits
+#[doc(hidden)]
+pub trait __WaiterUpcast<T: ?Sized>: Provider<T> {
+    fn __waiter_upcast(component: Wrc<Self::Impl>) -> Wrc<T>;
+    fn __waiter_upcast_boxed(component: Box<Self::Impl>) -> Box<T>;
+}
+
+impl<T, C: Provider<T, Impl = T>> __WaiterUpcast<T> for C {
+    fn __waiter_upcast(component: Wrc<T>) -> Wrc<T> {
+        component
+    }
+    fn __waiter_upcast_boxed(component: Box<T>) -> Box<T> {
+        component
+    }
+}
+
+/// Provider of a component named with `#[provides(name = "...")]`,
+/// `NAME` is a hash of the name generated by macros
+#[diagnostic::on_unimplemented(
+    message = "Component requested with #[inject(\"...\")] is not provided as `{T}` for `{Self}`",
+    note = "use #[provides(name = \"...\")] on the impl block to provide a named component",
+)]
+pub trait NamedProvider<T: ?Sized, const NAME: u64> {
+    type Impl;
+    fn try_get(&mut self) -> Result<Wrc<Self::Impl>, ResolveError>;
+    fn try_create(&mut self) -> Result<Self::Impl, ResolveError>;
+}
+
 pub struct Container<P> {
-    profile: PhantomData<P>,
+    /// The container doesn't own a profile value, so it's `Send` for any profile type
+    profile: PhantomData<fn() -> P>,
+    /// Type name of the profile the container was created with, it's kept when the state is
+    /// moved to a container with another profile, so bindings are selected by the original one
+    pub(crate) profile_type: &'static str,
+    profiles: Vec<String>,
     pub config: Config,
-    pub components: HashMap<TypeId, RcAny>,
+    pub(crate) env_vars: Vec<EnvVar>,
+    pub(crate) loader: Arc<ConfigLoader>,
+    pub(crate) live: Arc<LiveConfig>,
+    pub(crate) components: Components,
+    /// Only the container that created the components closes them, child containers share them
+    pub(crate) owns_components: bool,
+    /// Request scope is available only in child containers
+    pub(crate) is_child: bool,
+    pub(crate) overrides: HashMap<TypeId, RcAny>,
+    pub(crate) create_overrides: HashMap<TypeId, RcAny>,
+    pub(crate) scopes: HashMap<TypeId, Box<dyn ErasedScope>>,
+    pub(crate) constructing: Vec<(TypeId, &'static str)>,
+    pub(crate) pending: Vec<PendingSingleton>,
+    pub(crate) initializing: bool,
+    /// Singletons being created or initialized, components of custom scopes can't be injected
+    pub(crate) building_singletons: Vec<&'static str>,
+    #[cfg(feature = "async")]
+    pub(crate) postponed: Vec<PostponedHook>,
+}
+
+#[cfg(feature = "async")]
+type Lock<T> = std::sync::Mutex<T>;
+
+#[cfg(not(feature = "async"))]
+type Lock<T> = std::cell::RefCell<T>;
+
+/// Singletons of the container, cloned handles share them, e.g. with child containers
+#[derive(Clone, Default)]
+pub struct Components {
+    state: Wrc<Lock<ComponentsState>>,
+    #[cfg(feature = "async")]
+    construction: Arc<Construction>,
+}
+
+/// Lets one thread at a time create singletons, so containers sharing the components on
+/// different threads don't create a singleton twice and don't inject it before it's initialized.
+/// The thread can lock it again, since singletons resolve their dependencies while they are
+/// created. A lock per type would deadlock on singletons depending on each other with `Deferred`
+#[cfg(feature = "async")]
+#[derive(Default)]
+struct Construction {
+    owner: std::sync::Mutex<Option<(std::thread::ThreadId, usize)>>,
+    released: std::sync::Condvar,
+}
+
+/// Held while the singleton is created, other threads wait for it to create their singletons
+#[doc(hidden)]
+pub struct ConstructionGuard {
+    #[cfg(feature = "async")]
+    construction: Arc<Construction>,
+}
+
+#[cfg(feature = "async")]
+impl Drop for ConstructionGuard {
+    fn drop(&mut self) {
+        let mut owner = self.construction.owner.lock().unwrap_or_else(PoisonError::into_inner);
+        *owner = match *owner {
+            Some((thread, depth)) if depth > 1 => Some((thread, depth - 1)),
+            _ => None
+        };
+        if owner.is_none() {
+            self.construction.released.notify_all();
+        }
+    }
+}
+
+#[derive(Default)]
+struct ComponentsState {
+    components: HashMap<TypeId, RcAny>,
+    singletons: Vec<Singleton>,
+    closed: bool,
+}
+
+impl Components {
+    #[cfg(feature = "async")]
+    fn with<R>(&self, f: impl FnOnce(&mut ComponentsState) -> R) -> R {
+        f(&mut self.state.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    #[cfg(feature = "async")]
+    fn lock_construction(&self) -> ConstructionGuard {
+        let current = std::thread::current().id();
+        let mut owner = self.construction.owner.lock().unwrap_or_else(PoisonError::into_inner);
+        while matches!(*owner, Some((thread, _)) if thread != current) {
+            owner = self.construction.released.wait(owner).unwrap_or_else(PoisonError::into_inner);
+        }
+        let depth = owner.map_or(0, |(_, depth)| depth);
+        *owner = Some((current, depth + 1));
+        ConstructionGuard { construction: self.construction.clone() }
+    }
+
+    #[cfg(not(feature = "async"))]
+    fn lock_construction(&self) -> ConstructionGuard {
+        ConstructionGuard {}
+    }
+
+    #[cfg(not(feature = "async"))]
+    fn with<R>(&self, f: impl FnOnce(&mut ComponentsState) -> R) -> R {
+        f(&mut self.state.borrow_mut())
+    }
+
+    pub fn get(&self, type_id: &TypeId) -> Option<RcAny> {
+        self.with(|state| state.components.get(type_id).cloned())
+    }
+
+    pub fn contains_key(&self, type_id: &TypeId) -> bool {
+        self.with(|state| state.components.contains_key(type_id))
+    }
+
+    pub fn insert(&self, type_id: TypeId, component: RcAny) -> Option<RcAny> {
+        self.with(|state| state.components.insert(type_id, component))
+    }
+
+    pub fn remove(&self, type_id: &TypeId) -> Option<RcAny> {
+        self.with(|state| state.components.remove(type_id))
+    }
+
+    pub fn len(&self) -> usize {
+        self.with(|state| state.components.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.with(|state| state.components.is_empty())
+    }
+
+    pub(crate) fn register(&self, singleton: Singleton) {
+        self.with(|state| state.singletons.push(singleton));
+    }
+
+    #[cfg(feature = "async")]
+    pub(crate) fn forget(&self, type_id: TypeId) {
+        self.with(|state| {
+            state.components.remove(&type_id);
+            state.singletons.retain(|singleton| singleton.type_id != type_id);
+        });
+    }
+
+    /// Takes the last registered singleton out, so its hook is called without the lock
+    pub(crate) fn pop_singleton(&self) -> Option<(Singleton, Option<RcAny>)> {
+        self.with(|state| state.singletons.pop()
+            .map(|singleton| {
+                let component = state.components.remove(&singleton.type_id);
+                (singleton, component)
+            })
+        )
+    }
+
+    pub(crate) fn is_closed(&self) -> bool {
+        self.with(|state| state.closed)
+    }
+
+    pub(crate) fn close(&self) {
+        self.with(|state| state.closed = true);
+    }
+
+    pub(crate) fn clear(&self) {
+        self.with(|state| state.components.clear());
+    }
 }
 
 impl<P> Default for Container<P> {
@@ -56,23 +270,191 @@ impl<P> Default for Container<P> {
 
 impl<P> Container<P> {
     pub fn new() -> Container<P> {
-        let mut config = Config::builder()
-            .add_source(File::with_name("config/default").required(false));
-
-        let profile = profile_name::<P>();
-        if profile.ne(&"default".to_string()) {
-            config = config
-                .add_source(File::with_name(&format!("config/{}", profile))
-                    .required(false));
-        }
+        Self::try_new().unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    pub fn try_new() -> Result<Container<P>, ResolveError> {
+        Self::try_with_profiles(&[])
+    }
+
+    /// Activates additional profiles, that can be used in `#[provides(profile = "...")]`
+    /// conditions. Profile configs are applied in the same order after the config of `P`
+    pub fn with_profiles(profiles: &[&str]) -> Container<P> {
+        Self::try_with_profiles(profiles).unwrap_or_else(|error| panic!("{}", error))
+    }
 
-        let config = config.add_source(Environment::default()).add_source(parse_args());
-        let config = config.build().expect("Failed to load environment");
+    pub fn try_with_profiles(profiles: &[&str]) -> Result<Container<P>, ResolveError> {
+        Self::builder().profiles(profiles).try_build()
+    }
+
+    /// Builder to change the config directory and sources of properties
+    pub fn builder() -> ContainerBuilder<P> {
+        ContainerBuilder::new()
+    }
 
+    pub(crate) fn from_config(
+        profiles: Vec<String>,
+        config: Config,
+        env_vars: Vec<EnvVar>,
+        loader: ConfigLoader,
+    ) -> Container<P> {
         Container {
+            live: Arc::new(LiveConfig::new(config.clone())),
+            loader: Arc::new(loader),
             config,
-            profile: PhantomData::<P>,
-            components: HashMap::new(),
+            env_vars,
+            profile: PhantomData,
+            profile_type: type_name::<P>(),
+            profiles,
+            components: Components::default(),
+            owns_components: true,
+            is_child: false,
+            overrides: HashMap::new(),
+            create_overrides: HashMap::new(),
+            scopes: HashMap::new(),
+            constructing: vec!(),
+            pending: vec!(),
+            building_singletons: vec!(),
+            initializing: false,
+            #[cfg(feature = "async")]
+            postponed: vec!(),
+        }
+    }
+
+    /// Singletons created by the container, shared with its child containers
+    pub fn components(&self) -> &Components {
+        &self.components
+    }
+
+    pub fn active_profiles(&self) -> &[String] {
+        &self.profiles
+    }
+
+    /// Environment variables read as properties, values aren't kept since they may be secrets
+    pub fn env_vars(&self) -> &[EnvVar] {
+        &self.env_vars
+    }
+
+    /// Temporarily moves the state into a container with another profile, it's used to
+    /// call providers registered at runtime, which can't be generic over the profile
+    #[doc(hidden)]
+    pub fn __waiter_with_profile<Q, R>(&mut self, f: impl FnOnce(&mut Container<Q>) -> R) -> R {
+        let mut container = self.take::<Q>();
+        let result = f(&mut container);
+        *self = container.take::<P>();
+        result
+    }
+
+    /// Marks the component as being constructed, so the dependency cycle is reported instead
+    /// of the stack overflow
+    #[doc(hidden)]
+    pub fn __waiter_begin_construct(
+        &mut self,
+        type_id: TypeId,
+        type_name: &'static str,
+    ) -> Result<(), ResolveError> {
+        if let Some(cycle_start) = self.constructing.iter().position(|(id, _)| *id == type_id) {
+            let mut cycle: Vec<String> = self.constructing[cycle_start..].iter()
+                .map(|(_, name)| name.to_string())
+                .collect();
+            cycle.push(type_name.to_string());
+            return Err(ResolveError::dependency_cycle(cycle));
+        }
+        self.constructing.push((type_id, type_name));
+        Ok(())
+    }
+
+    /// Waits until singletons created by other threads are initialized, the returned guard
+    /// keeps other threads from creating singletons until it's dropped
+    #[doc(hidden)]
+    pub fn __waiter_lock_construction(&self) -> ConstructionGuard {
+        self.components.lock_construction()
+    }
+
+    /// Marks the singleton as being created, until `__waiter_end_singleton` is called
+    #[doc(hidden)]
+    pub fn __waiter_begin_singleton(&mut self, type_name: &'static str) {
+        self.building_singletons.push(type_name);
+    }
+
+    #[doc(hidden)]
+    pub fn __waiter_end_singleton(&mut self) {
+        self.building_singletons.pop();
+    }
+
+    /// Singletons created before the outermost component failed are removed, since they are
+    /// waiting for initialization
+    #[doc(hidden)]
+    pub fn __waiter_end_construct(&mut self, failed: bool) {
+        self.constructing.pop();
+        if failed && self.constructing.is_empty() && !self.initializing {
+            for pending in std::mem::take(&mut self.pending) {
+                self.components.remove(&pending.type_id);
+            }
+            #[cfg(feature = "async")]
+            self.postponed.clear();
+        }
+    }
+
+    /// Container for `Lazy` and `Factory`, which shares singletons with this container, so they
+    /// are created once, destroyed when this container is closed and can't be resolved after that.
+    /// Config and overrides are the ones the container had when it was detached
+    #[doc(hidden)]
+    pub fn __waiter_detach(&self) -> Container<()> {
+        self.share::<()>(self.components.clone())
+    }
+
+    /// Container with the same config and overrides, that uses the components
+    pub(crate) fn share<Q>(&self, components: Components) -> Container<Q> {
+        Container {
+            profile: PhantomData,
+            profile_type: self.profile_type,
+            profiles: self.profiles.clone(),
+            config: self.config.clone(),
+            env_vars: self.env_vars.clone(),
+            loader: self.loader.clone(),
+            live: self.live.clone(),
+            components,
+            owns_components: false,
+            is_child: self.is_child,
+            overrides: self.overrides.clone(),
+            create_overrides: self.create_overrides.clone(),
+            scopes: HashMap::new(),
+            constructing: vec!(),
+            pending: vec!(),
+            building_singletons: vec!(),
+            initializing: false,
+            #[cfg(feature = "async")]
+            postponed: vec!(),
+        }
+    }
+
+    pub(crate) fn into_profile<Q>(mut self) -> Container<Q> {
+        self.take::<Q>()
+    }
+
+    /// Moves the state out, so the emptied container has nothing to close when it's dropped
+    pub(crate) fn take<Q>(&mut self) -> Container<Q> {
+        Container {
+            profile: PhantomData,
+            profile_type: self.profile_type,
+            profiles: std::mem::take(&mut self.profiles),
+            config: std::mem::take(&mut self.config),
+            env_vars: std::mem::take(&mut self.env_vars),
+            loader: std::mem::take(&mut self.loader),
+            live: std::mem::take(&mut self.live),
+            components: std::mem::take(&mut self.components),
+            owns_components: std::mem::take(&mut self.owns_components),
+            is_child: std::mem::take(&mut self.is_child),
+            overrides: std::mem::take(&mut self.overrides),
+            create_overrides: std::mem::take(&mut self.create_overrides),
+            scopes: std::mem::take(&mut self.scopes),
+            constructing: std::mem::take(&mut self.constructing),
+            pending: std::mem::take(&mut self.pending),
+            building_singletons: std::mem::take(&mut self.building_singletons),
+            initializing: std::mem::take(&mut self.initializing),
+            #[cfg(feature = "async")]
+            postponed: std::mem::take(&mut self.postponed),
         }
     }
 }
@@ -100,30 +482,6 @@ fn parse_profile() -> String {
     parsed_profile
 }
 
-pub fn parse_args() -> Config {
-    let mut config = Config::builder();
-
-    let mut args = args().peekable();
-    loop {
-        let arg = args.next();
-        if arg.is_some() {
-            let arg = arg.unwrap();
-            if let Some(stripped) = arg.strip_prefix("--") {
-                let value = args.peek();
-                if value.is_none() || value.unwrap().starts_with("--") {
-                    config = config.set_override(stripped, true).unwrap();
-                } else {
-                    config = config.set_override(stripped, args.next().unwrap()).unwrap();
-                }
-            }
-        } else {
-            break;
-        }
-    }
-
-    config.build().expect("Config should be built")
-}
-
 pub fn profile_name<T>() -> String {
     let profile_type_name = type_name::<T>().to_lowercase();
 
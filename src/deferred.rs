@@ -1,12 +1,23 @@
+use std::any::type_name;
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
 use std::ops::Deref;
-use std::sync::Mutex;
+use std::sync::OnceLock;
 
-use crate::deferred::DeferredValue::{Initialized, WaitingForValue};
+use crate::ResolveError;
 
+/// Value injected after the component is created, so it can be used to break dependency cycles.
+/// It's written once and is read without locks
 pub struct Deferred<T> {
-    value: Mutex<DeferredValue<T>>,
+    value: OnceLock<T>,
 }
 
+/// Returned by `Deferred::init` if the value is already set, contains the rejected value
+#[derive(PartialEq)]
+pub struct AlreadyInitialized<T>(pub T);
+
+/// State of the value stored by `Deferred` before it was rebuilt on `OnceLock`
+#[deprecated(note = "Deferred doesn't expose its state, use Deferred::try_get instead")]
 pub enum DeferredValue<T> {
     Initialized(T),
     WaitingForValue,
@@ -14,10 +25,16 @@ pub enum DeferredValue<T> {
 
 impl<T> Deferred<T> {
     pub fn new() -> Self {
-        Self { value: Mutex::new(WaitingForValue) }
+        Self { value: OnceLock::new() }
     }
-    pub fn init(&self, value: T) {
-        *self.value.lock().unwrap() = Initialized(value);
+
+    pub fn init(&self, value: T) -> Result<(), AlreadyInitialized<T>> {
+        self.value.set(value).map_err(AlreadyInitialized)
+    }
+
+    /// Returns `None` before the value is injected
+    pub fn try_get(&self) -> Option<&T> {
+        self.value.get()
     }
 }
 
@@ -31,24 +48,38 @@ impl<T> Deref for Deferred<T> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
-        unsafe {
-            if let Initialized(value) = &*(self.value.lock().unwrap().deref() as *const DeferredValue<T>) {
-                value
-            } else {
-                panic!("Deferred value must be initialized before the first usage")
-            }
-        }
+        self.try_get().expect("Deferred value must be initialized before the first usage")
+    }
+}
+
+impl<T> Debug for AlreadyInitialized<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "AlreadyInitialized<{}>", type_name::<T>())
+    }
+}
+
+impl<T> Display for AlreadyInitialized<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Deferred<{}> is already initialized", type_name::<T>())
+    }
+}
+
+impl<T> Error for AlreadyInitialized<T> {}
+
+impl<T> From<AlreadyInitialized<T>> for ResolveError {
+    fn from(_: AlreadyInitialized<T>) -> Self {
+        ResolveError::already_initialized(type_name::<T>())
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::Deferred;
+    use crate::{AlreadyInitialized, Deferred, ResolveError};
 
     #[test]
     fn deref_after_init() {
         let deferred = Deferred::<&str>::new();
-        deferred.init("Initialized");
+        deferred.init("Initialized").unwrap();
         assert_eq!("Initialized", *deferred);
     }
 
@@ -58,4 +89,47 @@ mod tests {
         let deferred = Deferred::<&str>::new();
         assert_eq!("Initialized", *deferred);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn second_init_is_rejected() {
+        let deferred = Deferred::<&str>::new();
+        assert_eq!(None, deferred.try_get());
+        deferred.init("first").unwrap();
+
+        assert_eq!(Err(AlreadyInitialized("second")), deferred.init("second"));
+        assert_eq!(Some(&"first"), deferred.try_get());
+    }
+
+    #[test]
+    fn second_init_converts_to_resolve_error() {
+        let deferred = Deferred::<&str>::new();
+        deferred.init("first").unwrap();
+
+        let error = ResolveError::from(deferred.init("second").unwrap_err());
+        assert!(matches!(error, ResolveError::AlreadyInitialized { .. }));
+    }
+
+    #[test]
+    fn send_and_sync_if_value_is() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Deferred<std::sync::Arc<i32>>>();
+    }
+
+    /// Compiles only if `T` isn't `Send`, otherwise both impls apply and `_` is ambiguous
+    #[cfg(not(feature = "async"))]
+    trait AmbiguousIfSend<A> {
+        fn not_send() {}
+    }
+
+    #[cfg(not(feature = "async"))]
+    impl<T: ?Sized> AmbiguousIfSend<()> for T {}
+
+    #[cfg(not(feature = "async"))]
+    impl<T: ?Sized + Send> AmbiguousIfSend<u8> for T {}
+
+    #[test]
+    #[cfg(not(feature = "async"))]
+    fn not_send_if_value_is_not() {
+        <Deferred<std::rc::Rc<i32>> as AmbiguousIfSend<_>>::not_send();
+    }
+}
@@ -0,0 +1,245 @@
+use config::Config;
+
+use crate::{APP_PROFILE, Container, ContainerSafe, profile_name, Provider, ResolveError, Wrc, __WaiterUpcast};
+
+/// Tuple of profile types one of which can be selected at runtime, e.g.
+/// `(profiles::Default, profiles::Dev)`
+pub trait ProfileSet {
+    fn names() -> Vec<String>;
+    fn try_new(index: usize) -> Result<Container<()>, ResolveError>;
+    fn try_get_all<I: ?Sized + 'static>(
+        index: usize,
+        container: &mut Container<()>,
+    ) -> Result<Vec<Wrc<I>>, ResolveError>;
+    fn try_create_all<I: ?Sized + 'static>(
+        index: usize,
+        container: &mut Container<()>,
+    ) -> Result<Vec<Box<I>>, ResolveError>;
+}
+
+/// Implemented for profile sets, if `T` is provided for every profile in the set
+pub trait ProfileSetProvider<T: ?Sized>: ProfileSet {
+    fn try_get(index: usize, container: &mut Container<()>) -> Result<Wrc<T>, ResolveError>;
+    fn try_create(index: usize, container: &mut Container<()>) -> Result<Box<T>, ResolveError>;
+}
+
+macro_rules! profile_set {
+    ($($profile:ident),+) => {
+        impl<$($profile),+> ProfileSet for ($($profile,)+) {
+            fn names() -> Vec<String> {
+                vec!($(profile_name::<$profile>()),+)
+            }
+
+            #[allow(unused_assignments)]
+            fn try_new(index: usize) -> Result<Container<()>, ResolveError> {
+                let mut i = 0;
+                $(
+                    if index == i {
+                        return Container::<$profile>::try_new().map(Container::into_profile);
+                    }
+                    i += 1;
+                )+
+                unreachable!("Profile index is out of range")
+            }
+
+            #[allow(unused_assignments)]
+            fn try_get_all<I: ?Sized + 'static>(
+                index: usize,
+                container: &mut Container<()>,
+            ) -> Result<Vec<Wrc<I>>, ResolveError> {
+                let mut i = 0;
+                $(
+                    if index == i {
+                        return container.__waiter_with_profile(|container: &mut Container<$profile>| {
+                            container.try_get_all::<I>()
+                        });
+                    }
+                    i += 1;
+                )+
+                unreachable!("Profile index is out of range")
+            }
+
+            #[allow(unused_assignments)]
+            fn try_create_all<I: ?Sized + 'static>(
+                index: usize,
+                container: &mut Container<()>,
+            ) -> Result<Vec<Box<I>>, ResolveError> {
+                let mut i = 0;
+                $(
+                    if index == i {
+                        return container.__waiter_with_profile(|container: &mut Container<$profile>| {
+                            container.try_create_all::<I>()
+                        });
+                    }
+                    i += 1;
+                )+
+                unreachable!("Profile index is out of range")
+            }
+        }
+
+        impl<T: ?Sized, $($profile),+> ProfileSetProvider<T> for ($($profile,)+)
+            where $(Container<$profile>: __WaiterUpcast<T>),+
+        {
+            #[allow(unused_assignments)]
+            fn try_get(index: usize, container: &mut Container<()>) -> Result<Wrc<T>, ResolveError> {
+                let mut i = 0;
+                $(
+                    if index == i {
+                        return container.__waiter_with_profile(|container: &mut Container<$profile>| {
+                            Provider::<T>::try_get(container)
+                                .map(<Container<$profile> as __WaiterUpcast<T>>::__waiter_upcast)
+                        });
+                    }
+                    i += 1;
+                )+
+                unreachable!("Profile index is out of range")
+            }
+
+            #[allow(unused_assignments)]
+            fn try_create(index: usize, container: &mut Container<()>) -> Result<Box<T>, ResolveError> {
+                let mut i = 0;
+                $(
+                    if index == i {
+                        return container.__waiter_with_profile(|container: &mut Container<$profile>| {
+                            Provider::<T>::try_create(container)
+                                .map(|component| <Container<$profile> as __WaiterUpcast<T>>::__waiter_upcast_boxed(
+                                    Box::new(component)
+                                ))
+                        });
+                    }
+                    i += 1;
+                )+
+                unreachable!("Profile index is out of range")
+            }
+        }
+    }
+}
+
+profile_set!(P1);
+profile_set!(P1, P2);
+profile_set!(P1, P2, P3);
+profile_set!(P1, P2, P3, P4);
+profile_set!(P1, P2, P3, P4, P5);
+profile_set!(P1, P2, P3, P4, P5, P6);
+profile_set!(P1, P2, P3, P4, P5, P6, P7);
+profile_set!(P1, P2, P3, P4, P5, P6, P7, P8);
+
+/// Container with the profile selected at runtime from the set of profiles `PS`
+pub struct DynContainer<PS: ProfileSet> {
+    profile: usize,
+    container: Container<()>,
+    profiles: std::marker::PhantomData<PS>,
+}
+
+impl<PS: ProfileSet> DynContainer<PS> {
+    /// Uses profile from `--profile` arg, `PROFILE` env variable or `profile` property
+    pub fn new() -> DynContainer<PS> {
+        Self::try_new().unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    pub fn try_new() -> Result<DynContainer<PS>, ResolveError> {
+        Self::try_with_profile(APP_PROFILE.as_str())
+    }
+
+    pub fn try_with_profile(profile: &str) -> Result<DynContainer<PS>, ResolveError> {
+        let known_profiles = PS::names();
+        let index = known_profiles.iter()
+            .position(|known_profile| known_profile == profile)
+            .ok_or_else(|| ResolveError::unknown_profile(profile, known_profiles.clone()))?;
+
+        Ok(DynContainer {
+            profile: index,
+            container: PS::try_new(index)?,
+            profiles: std::marker::PhantomData,
+        })
+    }
+
+    pub fn profile(&self) -> String {
+        PS::names().remove(self.profile)
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.container.config
+    }
+
+    /// Replaces component of type `T` like `Container::override_with`
+    pub fn override_with<T: ?Sized + 'static>(&mut self, component: Wrc<T>) where Wrc<T>: ContainerSafe {
+        self.container.override_with(component);
+    }
+
+    pub fn override_create_with<T, F>(&mut self, factory: F)
+        where T: ?Sized + 'static,
+              F: Fn() -> Box<T> + ContainerSafe + 'static
+    {
+        self.container.override_create_with(factory);
+    }
+
+    pub fn try_get<T: ?Sized + 'static>(&mut self) -> Result<Wrc<T>, ResolveError> where PS: ProfileSetProvider<T> {
+        self.container.__waiter_ensure_open()?;
+        if let Some(component) = self.container.overridden::<T>() {
+            return Ok(component);
+        }
+        PS::try_get(self.profile, &mut self.container)
+    }
+
+    pub fn try_create<T: ?Sized + 'static>(&mut self) -> Result<Box<T>, ResolveError> where PS: ProfileSetProvider<T> {
+        self.container.__waiter_ensure_open()?;
+        if let Some(component) = self.container.overridden_create::<T>() {
+            return Ok(component);
+        }
+        PS::try_create(self.profile, &mut self.container)
+    }
+
+    pub fn get<T: ?Sized + 'static>(&mut self) -> Wrc<T> where PS: ProfileSetProvider<T> {
+        self.try_get::<T>().unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    pub fn create<T: ?Sized + 'static>(&mut self) -> Box<T> where PS: ProfileSetProvider<T> {
+        self.try_create::<T>().unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    pub fn try_get_all<I: ?Sized + 'static>(&mut self) -> Result<Vec<Wrc<I>>, ResolveError> {
+        PS::try_get_all::<I>(self.profile, &mut self.container)
+    }
+
+    pub fn try_create_all<I: ?Sized + 'static>(&mut self) -> Result<Vec<Box<I>>, ResolveError> {
+        PS::try_create_all::<I>(self.profile, &mut self.container)
+    }
+
+    pub fn get_all<I: ?Sized + 'static>(&mut self) -> Vec<Wrc<I>> {
+        self.try_get_all::<I>().unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    pub fn create_all<I: ?Sized + 'static>(&mut self) -> Vec<Box<I>> {
+        self.try_create_all::<I>().unwrap_or_else(|error| panic!("{}", error))
+    }
+}
+
+impl<PS: ProfileSet> Default for DynContainer<PS> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{DynContainer, profiles, ResolveError};
+
+    #[test]
+    fn select_profile_by_name() {
+        let container = DynContainer::<(profiles::Default, profiles::Dev)>::try_with_profile("dev")
+            .unwrap();
+
+        assert_eq!("dev", container.profile());
+    }
+
+    #[test]
+    fn unknown_profile() {
+        let error = DynContainer::<(profiles::Default, profiles::Dev)>::try_with_profile("prod")
+            .err()
+            .unwrap();
+
+        assert!(matches!(error, ResolveError::UnknownProfile { .. }));
+        assert_eq!("Unknown profile \"prod\", expected one of: default, dev", error.to_string());
+    }
+}
@@ -0,0 +1,113 @@
+use config::{ConfigError, Map, Source, Value};
+
+/// Environment variable that was read as a property, e.g. `APP_DB__URL` as `db.url`
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnvVar {
+    pub name: String,
+    pub key: String,
+}
+
+/// Reads environment variables starting with `{prefix}_`, the rest of the name is lowercased
+/// and split into nested keys by the separator. Without a prefix every variable is read
+#[derive(Debug, Clone)]
+pub(crate) struct EnvSource {
+    vars: Vec<(EnvVar, String)>,
+}
+
+impl EnvSource {
+    pub(crate) fn new(prefix: Option<&str>, separator: Option<&str>) -> Self {
+        let vars = std::env::vars_os()
+            .filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?)));
+        Self::from_vars(vars, prefix, separator)
+    }
+
+    pub(crate) fn from_vars(
+        vars: impl Iterator<Item = (String, String)>,
+        prefix: Option<&str>,
+        separator: Option<&str>,
+    ) -> Self {
+        let mut vars: Vec<(EnvVar, String)> = vars
+            .filter_map(|(name, value)| {
+                let key = property_key(&name, prefix, separator)?;
+                Some((EnvVar { name, key }, value))
+            })
+            .collect();
+        vars.sort_by(|(a, _), (b, _)| a.name.cmp(&b.name));
+        Self { vars }
+    }
+
+    pub(crate) fn vars(&self) -> Vec<EnvVar> {
+        self.vars.iter().map(|(var, _)| var.clone()).collect()
+    }
+}
+
+fn property_key(name: &str, prefix: Option<&str>, separator: Option<&str>) -> Option<String> {
+    let name = name.to_lowercase();
+    let name = match prefix {
+        Some(prefix) => name.strip_prefix(&format!("{}_", prefix.to_lowercase()))?,
+        None => name.as_str(),
+    };
+    if name.is_empty() {
+        return None;
+    }
+
+    match separator {
+        Some(separator) => {
+            let parts: Vec<&str> = name.split(&separator.to_lowercase()).collect();
+            if parts.iter().any(|part| part.is_empty()) {
+                return None;
+            }
+            Some(parts.join("."))
+        }
+        None => Some(name.to_string()),
+    }
+}
+
+impl Source for EnvSource {
+    fn clone_into_box(&self) -> Box<dyn Source + Send + Sync> {
+        Box::new(self.clone())
+    }
+
+    fn collect(&self) -> Result<Map<String, Value>, ConfigError> {
+        let origin = "the environment".to_string();
+        Ok(self.vars.iter()
+            .map(|(var, value)| (var.key.clone(), Value::new(Some(&origin), value.as_str())))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::env::EnvSource;
+    use crate::EnvVar;
+
+    fn keys(prefix: Option<&str>, separator: Option<&str>) -> Vec<(String, String)> {
+        let vars = vec!(
+            ("APP_DB__URL", "postgres://"),
+            ("APP_PORT", "8080"),
+            ("APP_DB____URL", "empty key part"),
+            ("APPLICATION", "other"),
+            ("PATH", "/usr/bin"),
+        );
+        let source = EnvSource::from_vars(
+            vars.into_iter().map(|(name, value)| (name.to_string(), value.to_string())),
+            prefix,
+            separator,
+        );
+        source.vars().into_iter().map(|EnvVar { name, key }| (name, key)).collect()
+    }
+
+    #[test]
+    fn prefixed_nested_keys() {
+        assert_eq!(vec!(
+            ("APP_DB__URL".to_string(), "db.url".to_string()),
+            ("APP_PORT".to_string(), "port".to_string()),
+        ), keys(Some("app"), Some("__")));
+    }
+
+    #[test]
+    fn every_variable_without_prefix() {
+        assert_eq!(5, keys(None, None).len());
+        assert!(keys(None, None).contains(&("PATH".to_string(), "path".to_string())));
+    }
+}
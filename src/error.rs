@@ -0,0 +1,342 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+use config::ConfigError;
+
+pub type FactoryError = Box<dyn Error + Send + Sync>;
+
+#[derive(Debug)]
+pub enum ResolveError {
+    MissingProperty {
+        path: Vec<String>,
+        property: String,
+    },
+    InvalidProperty {
+        path: Vec<String>,
+        property: String,
+        type_name: String,
+        cause: String,
+    },
+    ConfigLoad {
+        path: Vec<String>,
+        cause: String,
+    },
+    Factory {
+        path: Vec<String>,
+        cause: FactoryError,
+    },
+    PostConstruct {
+        path: Vec<String>,
+        cause: FactoryError,
+    },
+    TypeMismatch {
+        path: Vec<String>,
+        type_name: String,
+    },
+    UnknownProfile {
+        path: Vec<String>,
+        profile: String,
+        known_profiles: Vec<String>,
+    },
+    NoProvider {
+        path: Vec<String>,
+        type_name: String,
+    },
+    AmbiguousProvider {
+        path: Vec<String>,
+        type_name: String,
+        candidates: Vec<String>,
+    },
+    InvalidCondition {
+        path: Vec<String>,
+        condition: String,
+        cause: String,
+    },
+    ContainerClosed {
+        path: Vec<String>,
+    },
+    DependencyCycle {
+        path: Vec<String>,
+        cycle: Vec<String>,
+    },
+    AsyncComponent {
+        path: Vec<String>,
+        type_name: String,
+    },
+    Overridden {
+        path: Vec<String>,
+        type_name: String,
+    },
+    InactiveScope {
+        path: Vec<String>,
+        scope: String,
+    },
+    /// `Deferred` or `WeakRef` dependency was injected twice
+    AlreadyInitialized {
+        path: Vec<String>,
+        type_name: String,
+    },
+    /// `get_ref` of a component that isn't kept by the container
+    NotSingleton {
+        path: Vec<String>,
+        type_name: String,
+    },
+    /// Component of a custom scope would be kept by the singleton after its scope ends
+    CaptiveDependency {
+        path: Vec<String>,
+        singleton: String,
+        scope: String,
+    },
+}
+
+impl ResolveError {
+    pub fn missing_property(property: &str) -> Self {
+        ResolveError::MissingProperty { path: vec!(), property: property.to_string() }
+    }
+
+    pub fn invalid_property(property: &str, type_name: &str, cause: impl Display) -> Self {
+        ResolveError::InvalidProperty {
+            path: vec!(),
+            property: property.to_string(),
+            type_name: type_name.to_string(),
+            cause: cause.to_string(),
+        }
+    }
+
+    pub fn config_load(cause: impl Display) -> Self {
+        ResolveError::ConfigLoad { path: vec!(), cause: cause.to_string() }
+    }
+
+    pub fn factory(cause: impl Into<FactoryError>) -> Self {
+        ResolveError::Factory { path: vec!(), cause: cause.into() }
+    }
+
+    pub fn post_construct(cause: impl Into<FactoryError>) -> Self {
+        ResolveError::PostConstruct { path: vec!(), cause: cause.into() }
+    }
+
+    pub fn type_mismatch(type_name: &str) -> Self {
+        ResolveError::TypeMismatch { path: vec!(), type_name: type_name.to_string() }
+    }
+
+    pub fn unknown_profile(profile: &str, known_profiles: Vec<String>) -> Self {
+        ResolveError::UnknownProfile { path: vec!(), profile: profile.to_string(), known_profiles }
+    }
+
+    pub fn no_provider(type_name: &str) -> Self {
+        ResolveError::NoProvider { path: vec!(), type_name: type_name.to_string() }
+    }
+
+    pub fn ambiguous_provider(type_name: &str, candidates: Vec<String>) -> Self {
+        ResolveError::AmbiguousProvider { path: vec!(), type_name: type_name.to_string(), candidates }
+    }
+
+    pub fn invalid_condition(condition: &str, cause: impl Display) -> Self {
+        ResolveError::InvalidCondition {
+            path: vec!(),
+            condition: condition.to_string(),
+            cause: cause.to_string(),
+        }
+    }
+
+    pub fn container_closed() -> Self {
+        ResolveError::ContainerClosed { path: vec!() }
+    }
+
+    pub fn dependency_cycle(cycle: Vec<String>) -> Self {
+        ResolveError::DependencyCycle { path: vec!(), cycle }
+    }
+
+    pub fn async_component(type_name: &str) -> Self {
+        ResolveError::AsyncComponent { path: vec!(), type_name: type_name.to_string() }
+    }
+
+    pub fn overridden(type_name: &str) -> Self {
+        ResolveError::Overridden { path: vec!(), type_name: type_name.to_string() }
+    }
+
+    pub fn inactive_scope(scope: &str) -> Self {
+        ResolveError::InactiveScope { path: vec!(), scope: scope.to_string() }
+    }
+
+    pub fn already_initialized(type_name: &str) -> Self {
+        ResolveError::AlreadyInitialized { path: vec!(), type_name: type_name.to_string() }
+    }
+
+    pub fn not_singleton(type_name: &str) -> Self {
+        ResolveError::NotSingleton { path: vec!(), type_name: type_name.to_string() }
+    }
+
+    pub fn captive_dependency(singleton: &str, scope: &str) -> Self {
+        ResolveError::CaptiveDependency {
+            path: vec!(),
+            singleton: singleton.to_string(),
+            scope: scope.to_string(),
+        }
+    }
+
+    /// Splits result of a config lookup into a missing property (`None`) and a property
+    /// that exists but can't be read as the requested type (`Err`)
+    pub fn check_property<T>(
+        lookup: Result<T, ConfigError>,
+        property: &str,
+        type_name: &str,
+    ) -> Result<Option<T>, Self> {
+        match lookup {
+            Ok(value) => Ok(Some(value)),
+            Err(ConfigError::NotFound(_)) => Ok(None),
+            Err(cause) => Err(Self::invalid_property(property, type_name, cause))
+        }
+    }
+
+    /// Components that were being resolved when the error occurred, from the outermost one
+    pub fn path(&self) -> &[String] {
+        match self {
+            ResolveError::MissingProperty { path, .. } => path,
+            ResolveError::InvalidProperty { path, .. } => path,
+            ResolveError::ConfigLoad { path, .. } => path,
+            ResolveError::Factory { path, .. } => path,
+            ResolveError::PostConstruct { path, .. } => path,
+            ResolveError::TypeMismatch { path, .. } => path,
+            ResolveError::UnknownProfile { path, .. } => path,
+            ResolveError::NoProvider { path, .. } => path,
+            ResolveError::AmbiguousProvider { path, .. } => path,
+            ResolveError::InvalidCondition { path, .. } => path,
+            ResolveError::ContainerClosed { path } => path,
+            ResolveError::DependencyCycle { path, .. } => path,
+            ResolveError::AsyncComponent { path, .. } => path,
+            ResolveError::Overridden { path, .. } => path,
+            ResolveError::InactiveScope { path, .. } => path,
+            ResolveError::AlreadyInitialized { path, .. } => path,
+            ResolveError::NotSingleton { path, .. } => path,
+            ResolveError::CaptiveDependency { path, .. } => path,
+        }
+    }
+
+    /// Adds a component that depends on the failed one to the beginning of the dependency path
+    pub fn within(mut self, component: &str) -> Self {
+        self.path_mut().insert(0, component.to_string());
+        self
+    }
+
+    fn path_mut(&mut self) -> &mut Vec<String> {
+        match self {
+            ResolveError::MissingProperty { path, .. } => path,
+            ResolveError::InvalidProperty { path, .. } => path,
+            ResolveError::ConfigLoad { path, .. } => path,
+            ResolveError::Factory { path, .. } => path,
+            ResolveError::PostConstruct { path, .. } => path,
+            ResolveError::TypeMismatch { path, .. } => path,
+            ResolveError::UnknownProfile { path, .. } => path,
+            ResolveError::NoProvider { path, .. } => path,
+            ResolveError::AmbiguousProvider { path, .. } => path,
+            ResolveError::InvalidCondition { path, .. } => path,
+            ResolveError::ContainerClosed { path } => path,
+            ResolveError::DependencyCycle { path, .. } => path,
+            ResolveError::AsyncComponent { path, .. } => path,
+            ResolveError::Overridden { path, .. } => path,
+            ResolveError::InactiveScope { path, .. } => path,
+            ResolveError::AlreadyInitialized { path, .. } => path,
+            ResolveError::NotSingleton { path, .. } => path,
+            ResolveError::CaptiveDependency { path, .. } => path,
+        }
+    }
+}
+
+impl Display for ResolveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ResolveError::MissingProperty { property, .. } =>
+                write!(f, "Property \"{}\" not found", property)?,
+            ResolveError::InvalidProperty { property, type_name, cause, .. } =>
+                if property.is_empty() {
+                    write!(f, "Can't parse config as '{}': {}", type_name, cause)?
+                } else {
+                    write!(f, "Can't parse prop '{}' as '{}': {}", property, type_name, cause)?
+                },
+            ResolveError::ConfigLoad { cause, .. } =>
+                write!(f, "Failed to load environment: {}", cause)?,
+            ResolveError::Factory { cause, .. } =>
+                write!(f, "Factory failed: {}", cause)?,
+            ResolveError::PostConstruct { cause, .. } =>
+                write!(f, "Post construct failed: {}", cause)?,
+            ResolveError::TypeMismatch { type_name, .. } =>
+                write!(f, "Component stored in container is not '{}'", type_name)?,
+            ResolveError::UnknownProfile { profile, known_profiles, .. } =>
+                write!(f, "Unknown profile \"{}\", expected one of: {}", profile, known_profiles.join(", "))?,
+            ResolveError::NoProvider { type_name, .. } =>
+                write!(f, "No active provider of '{}'", type_name)?,
+            ResolveError::AmbiguousProvider { type_name, candidates, .. } =>
+                write!(f, "Several active providers of '{}': {}", type_name, candidates.join(", "))?,
+            ResolveError::InvalidCondition { condition, cause, .. } =>
+                write!(f, "Invalid condition \"{}\": {}", condition, cause)?,
+            ResolveError::ContainerClosed { .. } =>
+                write!(f, "Container is closed")?,
+            ResolveError::DependencyCycle { cycle, .. } =>
+                write!(f, "Dependency cycle {}, inject one of them as Deferred to break it", cycle.join(" -> "))?,
+            ResolveError::AsyncComponent { type_name, .. } =>
+                write!(f, "'{}' is created asynchronously, resolve it with get_async", type_name)?,
+            ResolveError::Overridden { type_name, .. } =>
+                write!(f, "'{0}' is overridden, resolve it as Wrc<{0}> instead of its implementation", type_name)?,
+            ResolveError::InactiveScope { scope, .. } =>
+                write!(f, "Scope '{}' is active only in a child container", scope)?,
+            ResolveError::AlreadyInitialized { type_name, .. } =>
+                write!(f, "Deferred dependency '{}' is already initialized", type_name)?,
+            ResolveError::NotSingleton { type_name, .. } =>
+                write!(f, "'{}' isn't a singleton, so it can't be borrowed with get_ref, use get instead", type_name)?,
+            ResolveError::CaptiveDependency { singleton, scope, .. } =>
+                write!(f, "Singleton '{}' can't keep a component of scope '{}', inject it as Factory", singleton, scope)?,
+        }
+
+        if !self.path().is_empty() {
+            write!(f, " (while resolving {})", self.path().join(" -> "))?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ResolveError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ResolveError::Factory { cause, .. } => Some(cause.as_ref()),
+            ResolveError::PostConstruct { cause, .. } => Some(cause.as_ref()),
+            _ => None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use config::{Config, ConfigError};
+
+    use crate::ResolveError;
+
+    #[test]
+    fn path_is_built_from_outermost_component() {
+        let error = ResolveError::missing_property("db_url")
+            .within("Repository")
+            .within("Service");
+
+        assert_eq!(vec!("Service", "Repository"), error.path());
+        assert_eq!(
+            "Property \"db_url\" not found (while resolving Service -> Repository)",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn check_property_separates_missing_and_invalid() {
+        let config = Config::builder()
+            .set_override("str", "not a number").unwrap()
+            .build().unwrap();
+
+        let missing: Result<i64, ConfigError> = config.get_int("absent");
+        assert!(matches!(ResolveError::check_property(missing, "absent", "i64"), Ok(None)));
+
+        let invalid = config.get_int("str");
+        assert!(matches!(
+            ResolveError::check_property(invalid, "str", "i64"),
+            Err(ResolveError::InvalidProperty { .. })
+        ));
+    }
+}
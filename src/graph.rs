@@ -0,0 +1,343 @@
+use std::collections::{BTreeSet, HashSet};
+use std::fmt::Write;
+
+use config::Config;
+
+use crate::{Container, InterfaceBinding, ResolveError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DependencyKind {
+    Wrc,
+    Box,
+    /// Component injected by value, e.g. `dep: Dependency`
+    Value,
+    Vec,
+    Deferred,
+    Weak,
+    Lazy,
+    Factory,
+    Prop,
+    Config,
+    /// Edge from the interface to its active implementation
+    Implementation,
+}
+
+impl DependencyKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DependencyKind::Wrc => "wrc",
+            DependencyKind::Box => "box",
+            DependencyKind::Value => "value",
+            DependencyKind::Vec => "vec",
+            DependencyKind::Deferred => "deferred",
+            DependencyKind::Weak => "weak",
+            DependencyKind::Lazy => "lazy",
+            DependencyKind::Factory => "factory",
+            DependencyKind::Prop => "prop",
+            DependencyKind::Config => "config",
+            DependencyKind::Implementation => "implementation",
+        }
+    }
+}
+
+/// Reads the property the same way as it's injected, used by `Container::validate()`
+pub type PropertyCheck = fn(&Config) -> Result<(), ResolveError>;
+
+/// Field of a component or argument of a factory function, generated by macros
+pub struct DependencyInfo {
+    pub name: &'static str,
+    /// Requested type as it's written in the code
+    pub type_name: &'static str,
+    pub kind: DependencyKind,
+    pub qualifier: Option<&'static str>,
+    /// Injected component or interface, `None` for properties and config
+    pub target: Option<fn() -> &'static str>,
+    pub property: Option<&'static str>,
+    pub check: Option<PropertyCheck>,
+}
+
+/// Dependencies of a component, registered by `#[component]` and `#[provides]` on factories
+pub struct ComponentInfo {
+    pub component: fn() -> &'static str,
+    pub dependencies: &'static [DependencyInfo],
+}
+
+inventory::collect!(ComponentInfo);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum NodeKind {
+    Component,
+    Interface,
+    Property,
+    Config,
+}
+
+impl NodeKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            NodeKind::Component => "component",
+            NodeKind::Interface => "interface",
+            NodeKind::Property => "property",
+            NodeKind::Config => "config",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GraphNode {
+    pub name: String,
+    pub kind: NodeKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GraphEdge {
+    pub from: String,
+    pub to: String,
+    /// Field or argument name, name of the implementation for interfaces
+    pub field: String,
+    pub type_name: String,
+    pub kind: DependencyKind,
+    pub qualifier: Option<String>,
+}
+
+/// Components and their dependencies sorted by name, so the rendered graph is stable
+/// and can be compared between releases. Names are `std::any::type_name`, which isn't guaranteed
+/// to be the same for different compiler versions, so graphs should be compared when they are
+/// built with the same toolchain
+#[derive(Debug, Clone, PartialEq)]
+pub struct DependencyGraph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+impl<P> Container<P> {
+    /// Graph of all components with implementations of interfaces active for the container
+    pub fn dependency_graph(&self) -> DependencyGraph {
+        self.try_dependency_graph().unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Fails if a condition of an implementation can't be checked, e.g. its property can't be read
+    pub fn try_dependency_graph(&self) -> Result<DependencyGraph, ResolveError> {
+        let mut nodes = BTreeSet::new();
+        let mut edges = BTreeSet::new();
+
+        for info in inventory::iter::<ComponentInfo> {
+            let component = (info.component)().to_string();
+            nodes.insert(GraphNode { name: component.clone(), kind: NodeKind::Component });
+
+            for dependency in info.dependencies {
+                let (to, kind) = match (dependency.target, dependency.property) {
+                    (Some(target), _) => {
+                        let target = target();
+                        let kind = if target.starts_with("dyn ") {
+                            NodeKind::Interface
+                        } else {
+                            NodeKind::Component
+                        };
+                        (target.to_string(), kind)
+                    }
+                    (None, Some(property)) => (property.to_string(), NodeKind::Property),
+                    (None, None) => ("config".to_string(), NodeKind::Config),
+                };
+
+                nodes.insert(GraphNode { name: to.clone(), kind });
+                edges.insert(GraphEdge {
+                    from: component.clone(),
+                    to,
+                    field: dependency.name.to_string(),
+                    type_name: dependency.type_name.to_string(),
+                    kind: dependency.kind,
+                    qualifier: dependency.qualifier.map(str::to_string),
+                });
+            }
+        }
+
+        for binding in inventory::iter::<InterfaceBinding> {
+            if !binding.is_active(self)? {
+                continue;
+            }
+
+            let interface = (binding.interface_name)().to_string();
+            let component = (binding.component)().to_string();
+            // Components provided only for some profiles are bound to themselves
+            if interface == component {
+                continue;
+            }
+            nodes.insert(GraphNode { name: interface.clone(), kind: NodeKind::Interface });
+            nodes.insert(GraphNode { name: component.clone(), kind: NodeKind::Component });
+            edges.insert(GraphEdge {
+                from: interface.clone(),
+                to: component.clone(),
+                field: component,
+                type_name: interface,
+                kind: DependencyKind::Implementation,
+                qualifier: binding.name.map(str::to_string),
+            });
+        }
+
+        Ok(DependencyGraph {
+            nodes: nodes.into_iter().collect(),
+            edges: edges.into_iter().collect(),
+        })
+    }
+}
+
+impl DependencyGraph {
+    /// Part of the graph used by the component, e.g. by the root module
+    pub fn reachable_from(&self, root: &str) -> DependencyGraph {
+        let mut reachable = HashSet::new();
+        let mut queue = vec!(root.to_string());
+        while let Some(node) = queue.pop() {
+            if reachable.insert(node.clone()) {
+                queue.extend(self.edges.iter()
+                    .filter(|edge| edge.from == node)
+                    .map(|edge| edge.to.clone()));
+            }
+        }
+
+        DependencyGraph {
+            nodes: self.nodes.iter()
+                .filter(|node| reachable.contains(&node.name))
+                .cloned()
+                .collect(),
+            edges: self.edges.iter()
+                .filter(|edge| reachable.contains(&edge.from))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph dependencies {\n");
+        for node in &self.nodes {
+            let shape = match node.kind {
+                NodeKind::Component => "box",
+                NodeKind::Interface => "ellipse",
+                NodeKind::Property | NodeKind::Config => "note",
+            };
+            writeln!(dot, "    \"{}\" [shape={}];", escape(&node.name), shape).unwrap();
+        }
+        for edge in &self.edges {
+            let label = match (edge.kind, &edge.qualifier) {
+                (DependencyKind::Implementation, _) => "implementation".to_string(),
+                (kind, Some(qualifier)) =>
+                    format!("{}: {} \\\"{}\\\"", escape(&edge.field), kind.name(), escape(qualifier)),
+                (kind, None) => format!("{}: {}", escape(&edge.field), kind.name()),
+            };
+            let style = match edge.kind {
+                DependencyKind::Deferred | DependencyKind::Weak
+                | DependencyKind::Lazy | DependencyKind::Factory => ", style=dashed",
+                DependencyKind::Implementation => ", style=dotted",
+                _ => "",
+            };
+            writeln!(
+                dot,
+                "    \"{}\" -> \"{}\" [label=\"{}\"{}];",
+                escape(&edge.from),
+                escape(&edge.to),
+                label,
+                style
+            ).unwrap();
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    pub fn to_json(&self) -> String {
+        let nodes: Vec<String> = self.nodes.iter()
+            .map(|node| format!(
+                "    {{\"name\": \"{}\", \"kind\": \"{}\"}}",
+                escape(&node.name),
+                node.kind.name()
+            ))
+            .collect();
+        let edges: Vec<String> = self.edges.iter()
+            .map(|edge| format!(
+                "    {{\"from\": \"{}\", \"to\": \"{}\", \"field\": \"{}\", \"type\": \"{}\", \"kind\": \"{}\", \"qualifier\": {}}}",
+                escape(&edge.from),
+                escape(&edge.to),
+                escape(&edge.field),
+                escape(&edge.type_name),
+                edge.kind.name(),
+                edge.qualifier.as_ref()
+                    .map(|qualifier| format!("\"{}\"", escape(qualifier)))
+                    .unwrap_or_else(|| "null".to_string())
+            ))
+            .collect();
+
+        format!(
+            "{{\n  \"nodes\": [\n{}\n  ],\n  \"edges\": [\n{}\n  ]\n}}\n",
+            nodes.join(",\n"),
+            edges.join(",\n")
+        )
+    }
+}
+
+/// Escapes the string for quoted DOT ids and JSON strings
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(escaped, "\\u{:04x}", c as u32).unwrap(),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{DependencyGraph, DependencyKind, GraphEdge, GraphNode, NodeKind};
+    use crate::graph::escape;
+
+    fn graph() -> DependencyGraph {
+        DependencyGraph {
+            nodes: vec!(
+                GraphNode { name: "app::Service".to_string(), kind: NodeKind::Component },
+                GraphNode { name: "db.url".to_string(), kind: NodeKind::Property },
+            ),
+            edges: vec!(GraphEdge {
+                from: "app::Service".to_string(),
+                to: "db.url".to_string(),
+                field: "url".to_string(),
+                type_name: "String".to_string(),
+                kind: DependencyKind::Prop,
+                qualifier: None,
+            }),
+        }
+    }
+
+    #[test]
+    fn render_dot() {
+        assert_eq!(
+            "digraph dependencies {\n\
+            \x20   \"app::Service\" [shape=box];\n\
+            \x20   \"db.url\" [shape=note];\n\
+            \x20   \"app::Service\" -> \"db.url\" [label=\"url: prop\"];\n\
+            }\n",
+            graph().to_dot()
+        );
+    }
+
+    #[test]
+    fn escape_special_characters() {
+        assert_eq!("a\\\"b\\\\c\\nd\\te\\u0001", escape("a\"b\\c\nd\te\u{1}"));
+    }
+
+    #[test]
+    fn render_json() {
+        assert_eq!(
+            "{\n  \"nodes\": [\n\
+            \x20   {\"name\": \"app::Service\", \"kind\": \"component\"},\n\
+            \x20   {\"name\": \"db.url\", \"kind\": \"property\"}\n  ],\n  \"edges\": [\n\
+            \x20   {\"from\": \"app::Service\", \"to\": \"db.url\", \"field\": \"url\", \"type\": \"String\", \
+            \"kind\": \"prop\", \"qualifier\": null}\n  ]\n}\n",
+            graph().to_json()
+        );
+    }
+}
@@ -0,0 +1,90 @@
+use std::ops::Deref;
+use std::sync::{Mutex, OnceLock};
+
+use crate::{Container, ResolveError};
+
+pub type Resolve<T> = fn(&mut Container<()>) -> Result<T, ResolveError>;
+
+/// Dependency resolved on the first deref, e.g. `client: Lazy<Wrc<ExpensiveClient>>`
+pub struct Lazy<T> {
+    value: OnceLock<T>,
+    container: Mutex<Container<()>>,
+    resolve: Resolve<T>,
+}
+
+impl<T> Lazy<T> {
+    pub fn new(container: Container<()>, resolve: Resolve<T>) -> Self {
+        Self { value: OnceLock::new(), container: Mutex::new(container), resolve }
+    }
+
+    pub fn try_get(&self) -> Result<&T, ResolveError> {
+        if let Some(value) = self.value.get() {
+            return Ok(value);
+        }
+
+        let mut container = self.container.lock().unwrap();
+        if let Some(value) = self.value.get() {
+            return Ok(value);
+        }
+        let value = (self.resolve)(&mut container)?;
+        Ok(self.value.get_or_init(|| value))
+    }
+
+    pub fn is_resolved(&self) -> bool {
+        self.value.get().is_some()
+    }
+}
+
+impl<T> Deref for Lazy<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        self.try_get().unwrap_or_else(|error| panic!("{}", error))
+    }
+}
+
+/// Creates new instances on demand, e.g. `jobs: Factory<Job>` or `jobs: Factory<Box<dyn Job>>`
+pub struct Factory<T> {
+    container: Mutex<Container<()>>,
+    create: Resolve<T>,
+}
+
+impl<T> Factory<T> {
+    pub fn new(container: Container<()>, create: Resolve<T>) -> Self {
+        Self { container: Mutex::new(container), create }
+    }
+
+    pub fn try_create(&self) -> Result<T, ResolveError> {
+        (self.create)(&mut self.container.lock().unwrap())
+    }
+
+    pub fn create(&self) -> T {
+        self.try_create().unwrap_or_else(|error| panic!("{}", error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Container, Factory, Lazy, ResolveError};
+
+    #[test]
+    fn resolved_once() {
+        let lazy = Lazy::new(Container::<()>::new(), |_| Ok(vec!(1)));
+        assert!(!lazy.is_resolved());
+        assert_eq!(vec!(1), *lazy);
+        assert!(lazy.is_resolved());
+    }
+
+    #[test]
+    fn error_is_returned_until_resolved() {
+        let lazy: Lazy<i32> = Lazy::new(Container::<()>::new(), |_| Err(ResolveError::no_provider("i32")));
+        assert!(lazy.try_get().is_err());
+        assert!(!lazy.is_resolved());
+    }
+
+    #[test]
+    fn factory_creates_new_values() {
+        let factory = Factory::new(Container::<()>::new(), |container| Ok(container.active_profiles().len()));
+        assert_eq!(factory.create(), factory.create());
+    }
+}
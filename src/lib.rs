@@ -1,11 +1,57 @@
 use std::any::Any;
 
+pub use args::*;
+#[cfg(feature = "async")]
+pub use async_provider::*;
+pub use builder::*;
+pub use child::*;
+pub use condition::*;
 pub use container::*;
 pub use deferred::*;
+pub use dyn_container::*;
+pub use env::*;
+pub use error::*;
+pub use graph::*;
+pub use lazy::*;
+pub use lifecycle::*;
+pub use properties::*;
+pub use registry::*;
+pub use reload::*;
+pub use scope::*;
+#[cfg(feature = "async")]
+pub use shared::*;
+pub use validate::*;
+pub use weak_ref::*;
 pub use waiter_codegen::*;
 
+#[doc(hidden)]
+pub use inventory;
+#[doc(hidden)]
+pub use log;
+
+pub mod args;
+#[cfg(feature = "async")]
+pub mod async_provider;
+pub mod builder;
+pub mod child;
+pub mod condition;
 pub mod container;
 pub mod deferred;
+pub mod dyn_container;
+pub mod env;
+pub mod error;
+pub mod graph;
+pub mod lazy;
+pub mod lifecycle;
+pub mod overrides;
+pub mod properties;
+pub mod registry;
+pub mod reload;
+pub mod scope;
+#[cfg(feature = "async")]
+pub mod shared;
+pub mod validate;
+pub mod weak_ref;
 
 #[macro_use]
 pub mod inject;
@@ -21,4 +67,17 @@ pub type Wrc<T> = std::rc::Rc<T>;
 pub type RcAny = Wrc<dyn Any + Send + Sync>;
 
 #[cfg(not(feature = "async"))]
-pub type RcAny = Wrc<dyn Any>;
\ No newline at end of file
+pub type RcAny = Wrc<dyn Any>;
+
+/// Values stored in the container must be `Send + Sync` with `async` feature
+#[cfg(feature = "async")]
+pub trait ContainerSafe: Send + Sync {}
+
+#[cfg(feature = "async")]
+impl<T: ?Sized + Send + Sync> ContainerSafe for T {}
+
+#[cfg(not(feature = "async"))]
+pub trait ContainerSafe {}
+
+#[cfg(not(feature = "async"))]
+impl<T: ?Sized> ContainerSafe for T {}
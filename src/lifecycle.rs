@@ -0,0 +1,265 @@
+use std::any::TypeId;
+
+use crate::{Container, RcAny, ResolveError, Wrc};
+
+/// Calls `#[pre_destroy]` hook of the component, if it has one
+pub type PreDestroy = fn(&RcAny);
+
+/// Injects `Deferred` dependencies of the component and calls its `#[post_construct]` hook
+pub type Initializer = fn(&mut Container<()>, &RcAny) -> Result<(), ResolveError>;
+
+/// Awaits async `#[post_construct]` hook of the singleton
+#[cfg(feature = "async")]
+pub type AsyncPostConstruct = fn(RcAny) -> crate::BoxFuture<'static, Result<(), ResolveError>>;
+
+/// Async `#[post_construct]` hook of the singleton which `Deferred` dependencies are injected
+/// only when the outermost component is created
+#[cfg(feature = "async")]
+pub(crate) struct PostponedHook {
+    type_id: TypeId,
+    type_name: &'static str,
+    component: RcAny,
+    post_construct: AsyncPostConstruct,
+}
+
+/// Singleton which initialization is postponed until all components that are being
+/// constructed are created, so `Deferred` dependencies can refer to them. Singletons without
+/// `Deferred` dependencies are initialized at once, but wait to be registered after
+/// the pending ones they may depend on
+pub(crate) struct PendingSingleton {
+    pub(crate) type_id: TypeId,
+    type_name: &'static str,
+    component: RcAny,
+    initialize: Option<Initializer>,
+    pre_destroy: PreDestroy,
+}
+
+/// Singleton created by the container and the `#[pre_destroy]` hook of its type
+pub struct Singleton {
+    pub type_id: TypeId,
+    pub type_name: &'static str,
+    pub pre_destroy: PreDestroy,
+}
+
+/// Singleton that is still referenced outside of the container after it was closed
+#[derive(Debug, Clone, PartialEq)]
+pub struct LeakedComponent {
+    pub type_name: &'static str,
+    pub strong_count: usize,
+}
+
+/// Default lifecycle hooks, methods generated by `#[post_construct]` and `#[pre_destroy]`
+/// are inherent, so they are called instead of these ones
+#[doc(hidden)]
+pub trait __WaiterLifecycle {
+    fn __waiter_post_construct(&self) -> Result<(), ResolveError> {
+        Ok(())
+    }
+    fn __waiter_pre_destroy(&self) {}
+    #[cfg(feature = "async")]
+    fn __waiter_post_construct_async(&self) -> crate::BoxFuture<'_, Result<(), ResolveError>> {
+        Box::pin(std::future::ready(Ok(())))
+    }
+}
+
+impl<T: ?Sized> __WaiterLifecycle for T {}
+
+impl<P> Container<P> {
+    /// Called by providers when singleton is fully initialized, so singletons are registered
+    /// after their dependencies
+    #[doc(hidden)]
+    pub fn __waiter_register_singleton(
+        &mut self,
+        type_id: TypeId,
+        type_name: &'static str,
+        pre_destroy: PreDestroy,
+    ) {
+        self.components.register(Singleton { type_id, type_name, pre_destroy });
+    }
+
+    /// Forgets the singleton which async `#[post_construct]` hook failed
+    #[cfg(feature = "async")]
+    #[doc(hidden)]
+    pub fn __waiter_remove_singleton(&mut self, type_id: TypeId) {
+        self.components.forget(type_id);
+        self.pending.retain(|pending| pending.type_id != type_id);
+        self.postponed.retain(|postponed| postponed.type_id != type_id);
+    }
+
+    /// Awaits async `#[post_construct]` hook of the singleton after its `Deferred` dependencies
+    /// are injected. Hook of a pending singleton is postponed until the outermost component is
+    /// initialized, then postponed hooks are awaited in order before the hook of the outermost one.
+    /// If any of them fails, singletons which hooks weren't awaited are removed
+    #[cfg(feature = "async")]
+    #[doc(hidden)]
+    pub async fn __waiter_post_construct_singleton_async(
+        &mut self,
+        type_id: TypeId,
+        type_name: &'static str,
+        component: RcAny,
+        post_construct: AsyncPostConstruct,
+    ) -> Result<(), ResolveError> {
+        let is_pending = self.pending.iter()
+            .any(|pending| pending.type_id == type_id && pending.initialize.is_some());
+        if is_pending {
+            self.postponed.push(PostponedHook { type_id, type_name, component, post_construct });
+            return Ok(());
+        }
+
+        let is_outermost = self.pending.is_empty() && self.constructing.is_empty() && !self.initializing;
+        let mut hooks = if is_outermost {
+            std::mem::take(&mut self.postponed)
+        } else {
+            vec!()
+        };
+        hooks.push(PostponedHook { type_id, type_name, component, post_construct });
+
+        let mut hooks = hooks.into_iter();
+        while let Some(hook) = hooks.next() {
+            if let Err(error) = (hook.post_construct)(hook.component).await {
+                self.__waiter_remove_singleton(hook.type_id);
+                for skipped in hooks {
+                    self.__waiter_remove_singleton(skipped.type_id);
+                }
+                return Err(error.within(hook.type_name));
+            }
+        }
+        Ok(())
+    }
+
+    /// Initializes the singleton, or postpones it until the outermost component is created,
+    /// if it has `Deferred` dependencies. Singleton without them is initialized at once, so its
+    /// `#[post_construct]` hook is called before it's injected. If any pending singleton fails,
+    /// all of them are removed from the container
+    #[doc(hidden)]
+    #[allow(clippy::too_many_arguments)]
+    pub fn __waiter_initialize_singleton(
+        &mut self,
+        type_id: TypeId,
+        type_name: &'static str,
+        component: RcAny,
+        initialize: Initializer,
+        pre_destroy: PreDestroy,
+        has_deferred: bool,
+    ) -> Result<(), ResolveError> {
+        let is_outermost = self.constructing.is_empty() && !self.initializing;
+        let initialize = if has_deferred {
+            Some(initialize)
+        } else {
+            self.__waiter_begin_singleton(type_name);
+            let initialized = self.__waiter_with_profile(|container: &mut Container<()>| {
+                initialize(container, &component)
+            });
+            self.__waiter_end_singleton();
+            if let Err(error) = initialized {
+                self.components.remove(&type_id);
+                return Err(error.within(type_name));
+            }
+            if self.pending.is_empty() && !self.initializing {
+                self.__waiter_register_singleton(type_id, type_name, pre_destroy);
+                return Ok(());
+            }
+            None
+        };
+
+        self.pending.push(PendingSingleton { type_id, type_name, component, initialize, pre_destroy });
+        if !is_outermost {
+            return Ok(());
+        }
+
+        self.initializing = true;
+        let result = self.initialize_pending();
+        self.initializing = false;
+        result
+    }
+
+    /// Singletons queued while a singleton is initialized are its `Deferred` dependencies,
+    /// so they are initialized and registered before it
+    fn initialize_pending(&mut self) -> Result<(), ResolveError> {
+        let mut queued = std::mem::take(&mut self.pending);
+        while !queued.is_empty() {
+            let pending = queued.remove(0);
+            self.__waiter_begin_singleton(pending.type_name);
+            let result = match pending.initialize {
+                Some(initialize) => self.__waiter_with_profile(|container: &mut Container<()>| {
+                    initialize(container, &pending.component)
+                }),
+                None => Ok(())
+            };
+            self.__waiter_end_singleton();
+            let result = result.and_then(|_| self.initialize_pending());
+
+            if let Err(error) = result {
+                self.components.remove(&pending.type_id);
+                for failed in queued {
+                    self.components.remove(&failed.type_id);
+                }
+                #[cfg(feature = "async")]
+                self.postponed.clear();
+                return Err(error.within(pending.type_name));
+            }
+            self.__waiter_register_singleton(pending.type_id, pending.type_name, pending.pre_destroy);
+        }
+        Ok(())
+    }
+
+    #[doc(hidden)]
+    pub fn __waiter_ensure_open(&self) -> Result<(), ResolveError> {
+        if self.components.is_closed() {
+            Err(ResolveError::container_closed())
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.components.is_closed()
+    }
+
+    /// Calls `#[pre_destroy]` hooks and drops singletons in reverse creation order, so
+    /// components are destroyed before their dependencies. Returns singletons that are still
+    /// referenced outside of the container, components can't be resolved after that.
+    /// Child container closes only its scopes, singletons are closed by the parent
+    pub fn close(&mut self) -> Vec<LeakedComponent> {
+        for scope in self.scopes.values_mut() {
+            scope.close();
+        }
+        self.scopes.clear();
+        if !self.owns_components {
+            return vec!();
+        }
+        self.components.close();
+
+        let mut leaked = vec!();
+        while let Some((singleton, component)) = self.components.pop_singleton() {
+            if let Some(component) = component {
+                (singleton.pre_destroy)(&component);
+
+                let strong_count = Wrc::strong_count(&component);
+                if strong_count > 1 {
+                    log::warn!(
+                        "{} is still referenced {} time(s) after container is closed",
+                        singleton.type_name,
+                        strong_count - 1
+                    );
+                    leaked.push(LeakedComponent {
+                        type_name: singleton.type_name,
+                        strong_count: strong_count - 1,
+                    });
+                }
+            }
+        }
+        self.components.clear();
+        self.overrides.clear();
+        self.create_overrides.clear();
+        leaked
+    }
+}
+
+impl<P> Drop for Container<P> {
+    fn drop(&mut self) {
+        if !self.is_closed() {
+            self.close();
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use std::any::TypeId;
+
+use crate::{ContainerSafe, Container, RcAny, ResolveError, Wrc};
+
+#[cfg(feature = "async")]
+type CreateOverride<T> = Box<dyn Fn() -> Box<T> + Send + Sync>;
+
+#[cfg(not(feature = "async"))]
+type CreateOverride<T> = Box<dyn Fn() -> Box<T>>;
+
+impl<P> Container<P> {
+    /// Replaces component of type `T` with the instance, e.g. `override_with::<dyn Mailer>(...)`,
+    /// it's used instead of the provided component when `T` is requested as `Wrc<T>`
+    pub fn override_with<T: ?Sized + 'static>(&mut self, component: Wrc<T>) where Wrc<T>: ContainerSafe {
+        self.overrides.insert(TypeId::of::<T>(), Wrc::new(component) as RcAny);
+    }
+
+    /// Replaces component of type `T` requested as `Box<T>` or `T`
+    pub fn override_create_with<T, F>(&mut self, factory: F)
+        where T: ?Sized + 'static,
+              F: Fn() -> Box<T> + ContainerSafe + 'static
+    {
+        let factory: CreateOverride<T> = Box::new(factory);
+        self.create_overrides.insert(TypeId::of::<T>(), Wrc::new(factory) as RcAny);
+    }
+
+    pub fn overridden<T: ?Sized + 'static>(&self) -> Option<Wrc<T>> {
+        self.overrides.get(&TypeId::of::<T>())
+            .and_then(|component| component.downcast_ref::<Wrc<T>>())
+            .cloned()
+    }
+
+    pub fn overridden_create<T: ?Sized + 'static>(&self) -> Option<Box<T>> {
+        self.create_overrides.get(&TypeId::of::<T>())
+            .and_then(|factory| factory.downcast_ref::<CreateOverride<T>>())
+            .map(|factory| factory())
+    }
+
+    /// Fails if `T` is overridden, it's used by providers of traits which return the implementation,
+    /// so the override can't be returned instead
+    #[doc(hidden)]
+    pub fn __waiter_ensure_not_overridden<T: ?Sized + 'static>(&self, create: bool) -> Result<(), ResolveError> {
+        let overrides = if create { &self.create_overrides } else { &self.overrides };
+        if overrides.contains_key(&TypeId::of::<T>()) {
+            Err(ResolveError::overridden(std::any::type_name::<T>()))
+        } else {
+            Ok(())
+        }
+    }
+}
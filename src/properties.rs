@@ -0,0 +1,18 @@
+use std::any::type_name;
+
+use config::Config;
+use serde::de::DeserializeOwned;
+
+use crate::ResolveError;
+
+/// Reads a section of the config, e.g. `database` for `#[config_properties(prefix = "database")]`.
+/// The whole config is read if the prefix is empty
+pub fn read_properties<T: DeserializeOwned>(config: &Config, prefix: &str) -> Result<T, ResolveError> {
+    if prefix.is_empty() {
+        return config.clone().try_deserialize::<T>()
+            .map_err(|error| ResolveError::invalid_property("", type_name::<T>(), error));
+    }
+
+    ResolveError::check_property(config.get::<T>(prefix), prefix, type_name::<T>())?
+        .ok_or_else(|| ResolveError::missing_property(prefix))
+}
@@ -0,0 +1,297 @@
+use std::any::{type_name, Any, TypeId};
+use std::marker::PhantomData;
+
+use crate::{Condition, Container, Provider, ResolveError, Wrc, __WaiterUpcast};
+
+pub type ErasedResolver = fn(&mut Container<()>) -> Result<Box<dyn Any>, ResolveError>;
+
+/// Registered by `#[provides]` for every impl block of a trait, so all implementations
+/// of the trait can be injected as `Vec<Wrc<dyn Trait>>` or `Vec<Box<dyn Trait>>`
+pub struct InterfaceBinding {
+    pub interface: fn() -> TypeId,
+    pub interface_name: fn() -> &'static str,
+    pub component: fn() -> &'static str,
+    pub profile: Option<fn() -> &'static str>,
+    pub order: Option<i32>,
+    pub name: Option<&'static str>,
+    pub conditions: &'static [Condition],
+    pub get: ErasedResolver,
+    pub create: ErasedResolver,
+}
+
+inventory::collect!(InterfaceBinding);
+
+impl InterfaceBinding {
+    pub(crate) fn is_active<P>(&self, container: &Container<P>) -> Result<bool, ResolveError> {
+        self.is_active_within(container, &mut vec!())
+    }
+
+    /// `checking` are the types which active implementations are being looked for, fallbacks
+    /// for them aren't counted, so a fallback doesn't exclude itself
+    fn is_active_within<P>(&self, container: &Container<P>, checking: &mut Vec<TypeId>) -> Result<bool, ResolveError> {
+        let profile_matches = self.profile
+            .map(|profile| profile() == container.profile_type)
+            .unwrap_or(true);
+        if !profile_matches {
+            return Ok(false);
+        }
+
+        for condition in self.conditions {
+            let satisfied = match condition {
+                Condition::Missing(type_id) => {
+                    let type_id = type_id();
+                    !checking.contains(&type_id) && !has_active_binding_within(type_id, container, checking)?
+                }
+                condition => condition.check(container)?
+            };
+            if !satisfied {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Binding that can be injected as a single `Wrc<dyn Trait>`, not only as a part of `Vec`
+    pub(crate) fn is_primary(&self) -> bool {
+        self.name.is_none()
+    }
+
+    fn is_provided_for_all_profiles(&self) -> bool {
+        self.profile.is_none() && self.conditions.is_empty() && self.order.is_none()
+    }
+}
+
+/// Selects the binding injected as a single `Wrc<dyn Trait>` from active ones: the one provided
+/// for all profiles, otherwise the only one with the lowest `order`
+pub(crate) fn select_binding<'b>(
+    interface: &str,
+    bindings: &[&'b InterfaceBinding],
+) -> Result<&'b InterfaceBinding, ResolveError> {
+    let candidates: Vec<&InterfaceBinding> = bindings.iter()
+        .copied()
+        .filter(|binding| binding.is_primary())
+        .collect();
+    let provided_for_all: Vec<&InterfaceBinding> = candidates.iter()
+        .copied()
+        .filter(|binding| binding.is_provided_for_all_profiles())
+        .collect();
+    match provided_for_all.len() {
+        0 => {}
+        1 => return Ok(provided_for_all[0]),
+        _ => return Err(ambiguous(interface, &provided_for_all)),
+    }
+
+    let lowest_order = candidates.iter().map(|binding| binding.order.unwrap_or_default()).min();
+    let mut selected: Vec<&InterfaceBinding> = candidates.into_iter()
+        .filter(|binding| Some(binding.order.unwrap_or_default()) == lowest_order)
+        .collect();
+
+    match selected.len() {
+        0 => Err(ResolveError::no_provider(interface)),
+        1 => Ok(selected.remove(0)),
+        _ => Err(ambiguous(interface, &selected))
+    }
+}
+
+fn ambiguous(interface: &str, bindings: &[&InterfaceBinding]) -> ResolveError {
+    ResolveError::ambiguous_provider(
+        interface,
+        bindings.iter().map(|binding| (binding.component)().to_string()).collect(),
+    )
+}
+
+/// Checks if there is an active implementation of the type, implementations that are provided
+/// only if another one of the same type is missing aren't counted
+pub(crate) fn has_active_binding<P>(interface: TypeId, container: &Container<P>) -> Result<bool, ResolveError> {
+    has_active_binding_within(interface, container, &mut vec!())
+}
+
+fn has_active_binding_within<P>(
+    interface: TypeId,
+    container: &Container<P>,
+    checking: &mut Vec<TypeId>,
+) -> Result<bool, ResolveError> {
+    checking.push(interface);
+    let mut found = Ok(false);
+    for binding in inventory::iter::<InterfaceBinding> {
+        if (binding.interface)() != interface {
+            continue;
+        }
+        found = binding.is_active_within(container, checking);
+        if !matches!(found, Ok(false)) {
+            break;
+        }
+    }
+    checking.pop();
+    found
+}
+
+fn active_bindings<I: ?Sized + 'static, P>(
+    container: &Container<P>
+) -> Result<Vec<&'static InterfaceBinding>, ResolveError> {
+    let interface = TypeId::of::<I>();
+
+    let mut bindings = vec!();
+    for binding in inventory::iter::<InterfaceBinding> {
+        if (binding.interface)() == interface && binding.is_active(container)? {
+            bindings.push(binding);
+        }
+    }
+
+    bindings.sort_by_key(|binding| (binding.order.unwrap_or_default(), (binding.component)()));
+    Ok(bindings)
+}
+
+impl<P> Container<P> {
+    /// Resolves all active implementations of a trait, an override of the trait replaces them
+    pub fn try_get_all<I: ?Sized + 'static>(&mut self) -> Result<Vec<Wrc<I>>, ResolveError> {
+        self.__waiter_ensure_open()?;
+        if let Some(component) = self.overridden::<I>() {
+            return Ok(vec!(component));
+        }
+
+        let bindings = active_bindings::<I, P>(self)?;
+        self.resolve_all::<Wrc<I>>(bindings, |binding| binding.get)
+    }
+
+    pub fn try_create_all<I: ?Sized + 'static>(&mut self) -> Result<Vec<Box<I>>, ResolveError> {
+        self.__waiter_ensure_open()?;
+        if let Some(component) = self.overridden_create::<I>() {
+            return Ok(vec!(component));
+        }
+
+        let bindings = active_bindings::<I, P>(self)?;
+        self.resolve_all::<Box<I>>(bindings, |binding| binding.create)
+    }
+
+    pub fn get_all<I: ?Sized + 'static>(&mut self) -> Vec<Wrc<I>> {
+        self.try_get_all::<I>().unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    pub fn create_all<I: ?Sized + 'static>(&mut self) -> Vec<Box<I>> {
+        self.try_create_all::<I>().unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Resolves the only implementation of a trait which conditions are met, it's used
+    /// for traits that have no implementation provided for all profiles
+    pub fn try_get_selected<I: ?Sized + 'static>(&mut self) -> Result<Wrc<I>, ResolveError> {
+        self.__waiter_ensure_open()?;
+        if let Some(component) = self.overridden::<I>() {
+            return Ok(component);
+        }
+
+        let binding = self.selected_binding::<I>()?;
+        self.resolve_all::<Wrc<I>>(vec!(binding), |binding| binding.get)
+            .map(|mut components| components.remove(0))
+    }
+
+    pub fn try_create_selected<I: ?Sized + 'static>(&mut self) -> Result<Box<I>, ResolveError> {
+        self.__waiter_ensure_open()?;
+        if let Some(component) = self.overridden_create::<I>() {
+            return Ok(component);
+        }
+
+        let binding = self.selected_binding::<I>()?;
+        self.resolve_all::<Box<I>>(vec!(binding), |binding| binding.create)
+            .map(|mut components| components.remove(0))
+    }
+
+    /// `Provider<dyn Trait>` is generated for one implementation without `order`, others are
+    /// only registered, so the trait is ambiguous if any of them is active too
+    #[doc(hidden)]
+    pub fn __waiter_ensure_single_provider<I: ?Sized + 'static>(&self) -> Result<(), ResolveError> {
+        let unordered: Vec<&InterfaceBinding> = active_bindings::<I, P>(self)?.into_iter()
+            .filter(|binding| binding.is_primary() && binding.order.is_none() && binding.conditions.is_empty())
+            .collect();
+        if unordered.len() > 1 {
+            return Err(ambiguous(type_name::<I>(), &unordered));
+        }
+        Ok(())
+    }
+
+    fn selected_binding<I: ?Sized + 'static>(&self) -> Result<&'static InterfaceBinding, ResolveError> {
+        select_binding(type_name::<I>(), &active_bindings::<I, P>(self)?)
+    }
+
+    fn resolve_all<T: 'static>(
+        &mut self,
+        bindings: Vec<&'static InterfaceBinding>,
+        resolver: fn(&InterfaceBinding) -> ErasedResolver,
+    ) -> Result<Vec<T>, ResolveError> {
+        self.__waiter_ensure_open()?;
+        self.__waiter_with_profile(|container: &mut Container<()>| {
+            bindings.iter()
+                .map(|binding| resolver(binding)(container)
+                    .and_then(|component| component.downcast::<T>()
+                        .map(|component| *component)
+                        .map_err(|_| ResolveError::type_mismatch(type_name::<T>()))
+                    )
+                )
+                .collect()
+        })
+    }
+}
+
+/// Used by macros to choose between a provider that is implemented for all profiles and
+/// the runtime selection of an implementation, which conditions are met
+#[doc(hidden)]
+pub struct __WaiterResolver<T: ?Sized>(PhantomData<T>);
+
+impl<T: ?Sized> __WaiterResolver<T> {
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T: ?Sized> Default for __WaiterResolver<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[doc(hidden)]
+pub trait __WaiterStaticResolve<T: ?Sized> {
+    fn __waiter_try_get<P>(&self, container: &mut Container<P>) -> Result<Wrc<T>, ResolveError>
+        where Container<P>: __WaiterUpcast<T>;
+    fn __waiter_try_create<P>(&self, container: &mut Container<P>) -> Result<Box<T>, ResolveError>
+        where Container<P>: __WaiterUpcast<T>;
+}
+
+impl<T: ?Sized + 'static> __WaiterStaticResolve<T> for __WaiterResolver<T> where Container<()>: __WaiterUpcast<T> {
+    fn __waiter_try_get<P>(&self, container: &mut Container<P>) -> Result<Wrc<T>, ResolveError>
+        where Container<P>: __WaiterUpcast<T>
+    {
+        container.__waiter_ensure_open()?;
+        if let Some(component) = container.overridden::<T>() {
+            return Ok(component);
+        }
+        Provider::<T>::try_get(container).map(<Container<P> as __WaiterUpcast<T>>::__waiter_upcast)
+    }
+
+    fn __waiter_try_create<P>(&self, container: &mut Container<P>) -> Result<Box<T>, ResolveError>
+        where Container<P>: __WaiterUpcast<T>
+    {
+        container.__waiter_ensure_open()?;
+        if let Some(component) = container.overridden_create::<T>() {
+            return Ok(component);
+        }
+        Provider::<T>::try_create(container)
+            .map(|component| <Container<P> as __WaiterUpcast<T>>::__waiter_upcast_boxed(Box::new(component)))
+    }
+}
+
+#[doc(hidden)]
+pub trait __WaiterRuntimeResolve<T: ?Sized> {
+    fn __waiter_try_get<P>(&self, container: &mut Container<P>) -> Result<Wrc<T>, ResolveError>;
+    fn __waiter_try_create<P>(&self, container: &mut Container<P>) -> Result<Box<T>, ResolveError>;
+}
+
+impl<T: ?Sized + 'static> __WaiterRuntimeResolve<T> for &__WaiterResolver<T> {
+    fn __waiter_try_get<P>(&self, container: &mut Container<P>) -> Result<Wrc<T>, ResolveError> {
+        container.try_get_selected::<T>()
+    }
+
+    fn __waiter_try_create<P>(&self, container: &mut Container<P>) -> Result<Box<T>, ResolveError> {
+        container.try_create_selected::<T>()
+    }
+}
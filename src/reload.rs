@@ -0,0 +1,270 @@
+use std::any::TypeId;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+
+use config::{Config, Value};
+use serde::de::DeserializeOwned;
+
+use crate::{read_properties, ConfigLoader, Container, EnvVar, ResolveError};
+
+/// Checks that the property can still be read as the type of a `Reloadable` field
+pub type ReloadCheck = fn(&Config, &str) -> Result<(), ResolveError>;
+
+type ChangeListener = Arc<dyn Fn(&Config) + Send + Sync>;
+
+/// Config shared by the container and `Reloadable` fields of its components
+#[derive(Default)]
+pub struct LiveConfig {
+    state: RwLock<LiveState>,
+}
+
+#[derive(Default)]
+struct LiveState {
+    config: Config,
+    env_vars: Vec<EnvVar>,
+    version: u64,
+    checks: Vec<PropertyCheck>,
+    listeners: Vec<(String, ChangeListener)>,
+}
+
+/// Check of a property shared by `Reloadable` fields of the same type, it's removed when
+/// the last of them is dropped, e.g. with a prototype component
+struct PropertyCheck {
+    key: String,
+    type_id: TypeId,
+    check: ReloadCheck,
+    fields: usize,
+}
+
+impl LiveConfig {
+    pub fn new(config: Config) -> Self {
+        Self {
+            state: RwLock::new(LiveState { config, ..LiveState::default() })
+        }
+    }
+
+    fn version(&self) -> u64 {
+        self.state.read().unwrap().version
+    }
+
+    fn register<T: DeserializeOwned + 'static>(&self, key: &str) {
+        let mut state = self.state.write().unwrap();
+        let type_id = TypeId::of::<T>();
+        match state.checks.iter_mut().find(|check| check.key == key && check.type_id == type_id) {
+            Some(check) => check.fields += 1,
+            None => state.checks.push(PropertyCheck {
+                key: key.to_string(),
+                type_id,
+                check: check_property::<T>,
+                fields: 1,
+            }),
+        }
+    }
+
+    fn unregister(&self, key: &str, type_id: TypeId) {
+        let mut state = self.state.write().unwrap();
+        if let Some(check) = state.checks.iter_mut().find(|check| check.key == key && check.type_id == type_id) {
+            check.fields -= 1;
+        }
+        state.checks.retain(|check| check.fields > 0);
+    }
+
+    /// Replaces the config if all `Reloadable` properties can be read from it, listeners are
+    /// called after the lock is released, so they can read `Reloadable` values
+    fn update(&self, config: Config, env_vars: Vec<EnvVar>) -> Result<Vec<String>, ResolveError> {
+        let mut notified: Vec<(String, ChangeListener)> = vec!();
+        {
+            let mut state = self.state.write().unwrap();
+            for check in &state.checks {
+                (check.check)(&config, &check.key)?;
+            }
+
+            for (key, listener) in &state.listeners {
+                if state.config.get::<Value>(key).ok() != config.get::<Value>(key).ok() {
+                    notified.push((key.clone(), listener.clone()));
+                }
+            }
+            state.config = config.clone();
+            state.env_vars = env_vars;
+            state.version += 1;
+        }
+
+        let mut changed = vec!();
+        for (key, listener) in notified {
+            listener(&config);
+            if !changed.contains(&key) {
+                changed.push(key);
+            }
+        }
+        Ok(changed)
+    }
+}
+
+fn check_property<T: DeserializeOwned>(config: &Config, key: &str) -> Result<(), ResolveError> {
+    read_properties::<T>(config, key).map(|_| ())
+}
+
+/// Property that follows reloads of the config, e.g.
+/// `#[prop("log.level")] level: Reloadable<String>`. Reload is rejected if the new value
+/// can't be read as `T`
+pub struct Reloadable<T> {
+    config: Arc<LiveConfig>,
+    key: String,
+    type_id: TypeId,
+    value: RwLock<(u64, Arc<T>)>,
+}
+
+impl<T: DeserializeOwned + 'static> Reloadable<T> {
+    /// Reads the value from the live config, it can be newer than `container.config`
+    /// if it was reloaded by `ConfigReloader`
+    pub fn new(live_config: Arc<LiveConfig>, key: &str) -> Result<Self, ResolveError> {
+        let (version, value) = {
+            let state = live_config.state.read().unwrap();
+            (state.version, read_properties::<T>(&state.config, key)?)
+        };
+        live_config.register::<T>(key);
+        Ok(Self {
+            config: live_config,
+            key: key.to_string(),
+            type_id: TypeId::of::<T>(),
+            value: RwLock::new((version, Arc::new(value))),
+        })
+    }
+
+    /// Current value, it's read again after the config is reloaded
+    pub fn get(&self) -> Arc<T> {
+        let version = self.config.version();
+        {
+            let value = self.value.read().unwrap();
+            if value.0 == version {
+                return value.1.clone();
+            }
+        }
+
+        let mut value = self.value.write().unwrap();
+        let state = self.config.state.read().unwrap();
+        match read_properties::<T>(&state.config, &self.key) {
+            Ok(reloaded) => *value = (state.version, Arc::new(reloaded)),
+            Err(error) => log::error!("Failed to reload {}: {}", self.key, error),
+        }
+        value.1.clone()
+    }
+
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+}
+
+impl<T> Drop for Reloadable<T> {
+    fn drop(&mut self) {
+        self.config.unregister(&self.key, self.type_id);
+    }
+}
+
+/// Reloads the config of the container without borrowing it, e.g. from a thread polling
+/// config files. Clones share the config with the container and its `Reloadable` properties
+#[derive(Clone)]
+pub struct ConfigReloader {
+    loader: Arc<ConfigLoader>,
+    live: Arc<LiveConfig>,
+}
+
+impl ConfigReloader {
+    /// Reads all config sources again, see `Container::reload_config`
+    pub fn reload(&self) -> Result<Vec<String>, ResolveError> {
+        let (config, env_vars) = self.loader.load()?;
+        self.live.update(config, env_vars)
+    }
+
+    /// Reloads the config if any of the watched files was changed, `None` if they weren't
+    pub fn reload_if_changed(&self, watch: &mut ConfigWatch) -> Result<Option<Vec<String>>, ResolveError> {
+        if watch.changed() {
+            self.reload().map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Watches files the config is read from
+    pub fn watch(&self) -> ConfigWatch {
+        ConfigWatch::new(self.loader.files())
+    }
+}
+
+/// Polls config files of the container, see `Container::config_watch` and `ConfigReloader::watch`
+pub struct ConfigWatch {
+    files: Vec<(PathBuf, Option<Vec<u8>>)>,
+}
+
+const EXTENSIONS: [&str; 7] = ["toml", "json", "yaml", "yml", "ini", "ron", "json5"];
+
+impl ConfigWatch {
+    pub fn new(files: Vec<PathBuf>) -> Self {
+        let files = files.into_iter()
+            .flat_map(|file| {
+                let with_extensions = EXTENSIONS.iter().map(|extension| {
+                    let mut path = file.clone().into_os_string();
+                    path.push(".");
+                    path.push(extension);
+                    PathBuf::from(path)
+                });
+                std::iter::once(file.clone()).chain(with_extensions).collect::<Vec<_>>()
+            })
+            .map(|file| {
+                let content = std::fs::read(&file).ok();
+                (file, content)
+            })
+            .collect();
+        Self { files }
+    }
+
+    /// Returns `true` if any file was created, changed or removed since the last call
+    pub fn changed(&mut self) -> bool {
+        let mut changed = false;
+        for (file, content) in &mut self.files {
+            let current = std::fs::read(&*file).ok();
+            if *content != current {
+                *content = current;
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
+impl<P> Container<P> {
+    /// Reads all config sources again. Components see new values of `Reloadable` properties,
+    /// other properties keep the values they were created with. If a `Reloadable` property can't
+    /// be read from the new config, it's rejected and the current config is kept.
+    /// Returns keys of `on_config_change` listeners that were notified
+    pub fn reload_config(&mut self) -> Result<Vec<String>, ResolveError> {
+        let changed = self.config_reloader().reload()?;
+        let state = self.live.state.read().unwrap();
+        self.config = state.config.clone();
+        self.env_vars = state.env_vars.clone();
+        Ok(changed)
+    }
+
+    /// Handle to reload the config from another thread. `Reloadable` properties and
+    /// `on_config_change` listeners follow it, `container.config` and other properties
+    /// of new components are updated only by `reload_config`
+    pub fn config_reloader(&self) -> ConfigReloader {
+        ConfigReloader { loader: self.loader.clone(), live: self.live.clone() }
+    }
+
+    /// Calls the listener after a reload that changed the property or any of its nested properties
+    pub fn on_config_change(&mut self, key: &str, listener: impl Fn(&Config) + Send + Sync + 'static) {
+        self.live.state.write().unwrap().listeners.push((key.to_string(), Arc::new(listener)));
+    }
+
+    /// Watches files the config is read from, to reload it when they are changed:
+    /// `if watch.changed() { container.reload_config()?; }`
+    pub fn config_watch(&self) -> ConfigWatch {
+        ConfigWatch::new(self.loader.files())
+    }
+
+    #[doc(hidden)]
+    pub fn __waiter_live_config(&self) -> Arc<LiveConfig> {
+        self.live.clone()
+    }
+}
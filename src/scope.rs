@@ -0,0 +1,156 @@
+use std::any::{type_name, Any, TypeId};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::thread::ThreadId;
+
+use crate::{Container, ContainerSafe, PreDestroy, RcAny, ResolveError};
+
+#[cfg(feature = "async")]
+pub type BoxAny = Box<dyn Any + Send + Sync>;
+
+#[cfg(not(feature = "async"))]
+pub type BoxAny = Box<dyn Any>;
+
+/// Caching policy of components declared with `#[component(scope = "path::to::Scope")]`.
+/// Scope is created with `Default` when it's used first time and is stored in the container
+pub trait Scope: ContainerSafe + 'static {
+    /// Returns cached component, new component is created if `None` is returned
+    fn get(&mut self, type_id: TypeId) -> Option<RcAny>;
+    fn put(&mut self, type_id: TypeId, component: RcAny);
+    /// Called if component failed to initialize after it was put to the scope
+    fn remove(&mut self, type_id: TypeId);
+    /// Called when component is initialized, scope may call `pre_destroy` when it discards
+    /// the component to run its `#[pre_destroy]` hook
+    fn initialized(&mut self, _type_id: TypeId, _pre_destroy: PreDestroy) {}
+    /// Called when the container is closed, scope should call `pre_destroy` of components
+    /// it still holds
+    fn close(&mut self) {}
+}
+
+/// Scope stored in the container, so it can be closed without knowing its type
+pub(crate) trait ErasedScope: ContainerSafe {
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    fn close(&mut self);
+}
+
+impl<S: Scope> ErasedScope for S {
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn close(&mut self) {
+        Scope::close(self)
+    }
+}
+
+/// Components that are destroyed in reverse creation order when the scope is closed
+struct Created<K> {
+    components: HashMap<K, RcAny>,
+    created: Vec<(K, PreDestroy)>,
+}
+
+impl<K> Default for Created<K> {
+    fn default() -> Self {
+        Self { components: HashMap::new(), created: vec!() }
+    }
+}
+
+impl<K: Eq + Hash + Copy> Created<K> {
+    fn destroy(&mut self) {
+        while let Some((key, pre_destroy)) = self.created.pop() {
+            if let Some(component) = self.components.remove(&key) {
+                pre_destroy(&component);
+            }
+        }
+        self.components.clear();
+    }
+}
+
+/// One instance of a component per thread, it's used for `#[component(scope = "thread")]`.
+/// Components are destroyed when the container is closed
+#[derive(Default)]
+pub struct ThreadScope {
+    components: Created<(ThreadId, TypeId)>,
+}
+
+impl Scope for ThreadScope {
+    fn get(&mut self, type_id: TypeId) -> Option<RcAny> {
+        self.components.components.get(&(std::thread::current().id(), type_id)).cloned()
+    }
+
+    fn put(&mut self, type_id: TypeId, component: RcAny) {
+        self.components.components.insert((std::thread::current().id(), type_id), component);
+    }
+
+    fn remove(&mut self, type_id: TypeId) {
+        self.components.components.remove(&(std::thread::current().id(), type_id));
+    }
+
+    fn initialized(&mut self, type_id: TypeId, pre_destroy: PreDestroy) {
+        self.components.created.push(((std::thread::current().id(), type_id), pre_destroy));
+    }
+
+    fn close(&mut self) {
+        self.components.destroy();
+    }
+}
+
+/// Components of `#[component(scope = "request")]` are cached in a child container and are
+/// destroyed in reverse creation order when the child container is dropped
+#[derive(Default)]
+pub struct RequestScope {
+    components: Created<TypeId>,
+}
+
+impl Scope for RequestScope {
+    fn get(&mut self, type_id: TypeId) -> Option<RcAny> {
+        self.components.components.get(&type_id).cloned()
+    }
+
+    fn put(&mut self, type_id: TypeId, component: RcAny) {
+        self.components.components.insert(type_id, component);
+    }
+
+    fn remove(&mut self, type_id: TypeId) {
+        self.components.components.remove(&type_id);
+    }
+
+    fn initialized(&mut self, type_id: TypeId, pre_destroy: PreDestroy) {
+        self.components.created.push((type_id, pre_destroy));
+    }
+
+    fn close(&mut self) {
+        self.components.destroy();
+    }
+}
+
+impl Drop for RequestScope {
+    fn drop(&mut self) {
+        self.components.destroy();
+    }
+}
+
+impl<P> Container<P> {
+    /// Request scope is active only in child containers, so request components aren't cached
+    /// by the parent forever. Components of custom scopes can't be injected into singletons,
+    /// which would keep them after the scope ends
+    #[doc(hidden)]
+    pub fn __waiter_ensure_scope_active<S: Scope>(&self) -> Result<(), ResolveError> {
+        if TypeId::of::<S>() == TypeId::of::<RequestScope>() && !self.is_child {
+            return Err(ResolveError::inactive_scope(type_name::<S>()));
+        }
+        match self.building_singletons.last() {
+            Some(singleton) => Err(ResolveError::captive_dependency(singleton, type_name::<S>())),
+            None => Ok(())
+        }
+    }
+
+    /// Scope of the container, e.g. to clear the cache of a custom scope
+    pub fn scope_mut<S: Scope + Default>(&mut self) -> &mut S {
+        self.scopes.entry(TypeId::of::<S>())
+            .or_insert_with(|| Box::new(S::default()))
+            .as_any_mut()
+            .downcast_mut::<S>()
+            .unwrap()
+    }
+}
@@ -0,0 +1,169 @@
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, RwLock};
+use std::thread::ThreadId;
+
+use crate::{Container, Provider, RcAny, ResolveError, Wrc};
+
+/// Container that can be cloned and shared between threads and tasks. Every thread resolves
+/// components with its own container, which shares singletons, config and overrides of the shared
+/// one, so prototypes are created in parallel. Singletons are created one at a time and exactly
+/// once, resolved singletons don't wait for it. Components must not resolve other components
+/// from the same shared container while they are constructed
+pub struct SharedContainer<P> {
+    state: Arc<SharedState<P>>,
+}
+
+struct SharedState<P> {
+    /// Dropped before the shared container, so their scopes are closed before singletons
+    workers: Mutex<HashMap<ThreadId, Worker<P>>>,
+    container: Mutex<Container<P>>,
+    singletons: RwLock<HashMap<TypeId, RcAny>>,
+    /// Changed by `with_container`, workers of older versions have outdated config and overrides
+    version: Mutex<usize>,
+}
+
+struct Worker<P> {
+    version: usize,
+    container: Container<P>,
+}
+
+impl<P> Drop for Worker<P> {
+    fn drop(&mut self) {
+        // Singletons waiting for initialization are removed, if a panic interrupted it
+        if std::thread::panicking() {
+            for pending in std::mem::take(&mut self.container.pending) {
+                self.container.components.remove(&pending.type_id);
+            }
+        }
+        // Scopes are closed even if singletons are already closed by the shared container
+        self.container.close();
+    }
+}
+
+impl<P> SharedContainer<P> {
+    pub fn new(container: Container<P>) -> Self {
+        Self {
+            state: Arc::new(SharedState {
+                workers: Mutex::new(HashMap::new()),
+                container: Mutex::new(container),
+                singletons: RwLock::new(HashMap::new()),
+                version: Mutex::new(0),
+            })
+        }
+    }
+
+    pub fn try_get<T>(&self) -> Result<Wrc<<Container<P> as Provider<T>>::Impl>, ResolveError>
+        where T: ?Sized + 'static,
+              Container<P>: Provider<T>,
+              <Container<P> as Provider<T>>::Impl: Send + Sync + 'static
+    {
+        if let Some(component) = self.resolved::<T, _>() {
+            return Ok(component);
+        }
+
+        self.with_worker(|version, container| {
+            let component = Provider::<T>::try_get(container)?;
+
+            // Components of other scopes are created for every call or cached by their scope
+            let is_singleton = container.components
+                .get(&TypeId::of::<<Container<P> as Provider<T>>::Impl>())
+                .is_some_and(|singleton| {
+                    Arc::as_ptr(&singleton) as *const () == Arc::as_ptr(&component) as *const ()
+                });
+            if is_singleton {
+                let mut singletons = self.state.singletons.write().unwrap_or_else(PoisonError::into_inner);
+                // Singletons resolved before the container was changed aren't cached
+                if version == *lock(&self.state.version) {
+                    singletons.insert(TypeId::of::<T>(), component.clone());
+                }
+            }
+            Ok(component)
+        })
+    }
+
+    pub fn get<T>(&self) -> Wrc<<Container<P> as Provider<T>>::Impl>
+        where T: ?Sized + 'static,
+              Container<P>: Provider<T>,
+              <Container<P> as Provider<T>>::Impl: Send + Sync + 'static
+    {
+        self.try_get::<T>().unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    pub fn try_create<T>(&self) -> Result<<Container<P> as Provider<T>>::Impl, ResolveError>
+        where T: ?Sized,
+              Container<P>: Provider<T>
+    {
+        self.with_worker(|_, container| Provider::<T>::try_create(container))
+    }
+
+    pub fn create<T>(&self) -> <Container<P> as Provider<T>>::Impl
+        where T: ?Sized,
+              Container<P>: Provider<T>
+    {
+        self.try_create::<T>().unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Locks the container, e.g. to add overrides or to close it. Containers of threads are
+    /// closed before, so components of their scopes are destroyed before singletons
+    pub fn with_container<R>(&self, f: impl FnOnce(&mut Container<P>) -> R) -> R {
+        let outdated = std::mem::take(&mut *lock(&self.state.workers));
+        drop(outdated);
+
+        let mut container = lock(&self.state.container);
+        let result = f(&mut container);
+        let mut singletons = self.state.singletons.write().unwrap_or_else(PoisonError::into_inner);
+        *lock(&self.state.version) += 1;
+        singletons.clear();
+        result
+    }
+
+    /// Resolves with the container of the current thread, the shared container is locked only
+    /// to create it
+    fn with_worker<R>(&self, f: impl FnOnce(usize, &mut Container<P>) -> R) -> R {
+        let thread = std::thread::current().id();
+        let worker = lock(&self.state.workers).remove(&thread);
+        let mut worker = match worker {
+            Some(worker) if worker.version == *lock(&self.state.version) => worker,
+            outdated => {
+                drop(outdated);
+                let container = lock(&self.state.container);
+                Worker {
+                    version: *lock(&self.state.version),
+                    container: container.share(container.components.clone()),
+                }
+            }
+        };
+
+        let result = f(worker.version, &mut worker.container);
+        if worker.version == *lock(&self.state.version) {
+            lock(&self.state.workers).insert(thread, worker);
+        }
+        result
+    }
+
+    fn resolved<T: ?Sized + 'static, I: Send + Sync + 'static>(&self) -> Option<Wrc<I>> {
+        self.state.singletons.read().unwrap_or_else(PoisonError::into_inner)
+            .get(&TypeId::of::<T>())
+            .cloned()
+            .and_then(|component| component.downcast::<I>().ok())
+    }
+}
+
+/// Lock poisoned by a panic of another thread is still used, since the state under it isn't
+/// left half-changed, e.g. a panicked worker isn't returned
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl<P> Clone for SharedContainer<P> {
+    fn clone(&self) -> Self {
+        Self { state: self.state.clone() }
+    }
+}
+
+impl<P> From<Container<P>> for SharedContainer<P> {
+    fn from(container: Container<P>) -> Self {
+        Self::new(container)
+    }
+}
@@ -0,0 +1,147 @@
+use std::any::type_name;
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+
+use config::Config;
+
+use crate::{ComponentInfo, Container, DependencyInfo, DependencyKind, InterfaceBinding, LiveConfig,
+            ResolveError};
+use crate::registry::select_binding;
+
+/// Used by the property checks generated by macros, which read props from `container.config`
+#[doc(hidden)]
+pub struct __WaiterConfigRef<'a> {
+    pub config: &'a Config,
+}
+
+impl __WaiterConfigRef<'_> {
+    /// `Reloadable` properties are checked against a config that is never reloaded
+    pub fn __waiter_live_config(&self) -> Arc<LiveConfig> {
+        Arc::new(LiveConfig::new(self.config.clone()))
+    }
+}
+
+/// All problems found by `Container::validate()`
+#[derive(Debug)]
+pub struct ValidationError {
+    pub problems: Vec<ResolveError>,
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Found {} problem(s):", self.problems.len())?;
+        for problem in &self.problems {
+            write!(f, "\n  {}", problem)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ValidationError {}
+
+impl<P> Container<P> {
+    /// Checks every dependency reachable from `Root` without creating components: properties
+    /// exist and can be read as declared types, interfaces have a provider active for the profile
+    /// of the container
+    pub fn validate<Root: ?Sized>(&self) -> Result<(), ValidationError> {
+        let mut validation = Validation { container: self, visited: HashSet::new(), problems: vec!() };
+        validation.visit(type_name::<Root>(), &mut vec!());
+
+        if validation.problems.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError { problems: validation.problems })
+        }
+    }
+}
+
+struct Validation<'c, P> {
+    container: &'c Container<P>,
+    visited: HashSet<&'static str>,
+    problems: Vec<ResolveError>,
+}
+
+impl<P> Validation<'_, P> {
+    fn visit(&mut self, component: &'static str, path: &mut Vec<&'static str>) {
+        if !self.visited.insert(component) {
+            return;
+        }
+
+        // Components without metadata, e.g. generic ones, are skipped
+        let info = match inventory::iter::<ComponentInfo>.into_iter().find(|info| (info.component)() == component) {
+            Some(info) => info,
+            None => return
+        };
+
+        path.push(component);
+        for dependency in info.dependencies {
+            if let Some(check) = dependency.check {
+                if let Err(error) = check(&self.container.config) {
+                    self.report(error, path);
+                }
+            }
+
+            let target = match dependency.target {
+                Some(target) => target(),
+                None => continue
+            };
+            if target.starts_with("dyn ") {
+                for implementation in self.implementations(target, dependency, path) {
+                    self.visit(implementation, path);
+                }
+            } else {
+                self.visit(target, path);
+            }
+        }
+        path.pop();
+    }
+
+    /// Implementations that would be injected, like they are selected by the container
+    fn implementations(
+        &mut self,
+        interface: &'static str,
+        dependency: &DependencyInfo,
+        path: &[&'static str],
+    ) -> Vec<&'static str> {
+        let mut bindings: Vec<&InterfaceBinding> = vec!();
+        for binding in inventory::iter::<InterfaceBinding> {
+            if (binding.interface_name)() != interface {
+                continue;
+            }
+            match binding.is_active(self.container) {
+                Ok(true) => bindings.push(binding),
+                Ok(false) => {}
+                Err(error) => self.report(error, path),
+            }
+        }
+
+        if dependency.kind == DependencyKind::Vec {
+            return bindings.iter().map(|binding| (binding.component)()).collect();
+        }
+
+        if let Some(qualifier) = dependency.qualifier {
+            return match bindings.iter().find(|binding| binding.name == Some(qualifier)) {
+                Some(binding) => vec!((binding.component)()),
+                None => {
+                    self.report(ResolveError::no_provider(&format!("{} named \"{}\"", interface, qualifier)), path);
+                    vec!()
+                }
+            };
+        }
+
+        match select_binding(interface, &bindings) {
+            Ok(binding) => vec!((binding.component)()),
+            Err(error) => {
+                self.report(error, path);
+                vec!()
+            }
+        }
+    }
+
+    fn report(&mut self, error: ResolveError, path: &[&'static str]) {
+        let error = path.iter().rev().fold(error, |error, component| error.within(component));
+        self.problems.push(error);
+    }
+}
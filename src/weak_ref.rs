@@ -0,0 +1,80 @@
+use std::marker::PhantomData;
+use std::sync::OnceLock;
+
+#[cfg(feature = "async")]
+use std::sync::Weak;
+#[cfg(not(feature = "async"))]
+use std::rc::Weak;
+
+use crate::{AlreadyInitialized, Wrc};
+
+/// Weak reference to a singleton injected after the component is created, e.g.
+/// `parent: WeakRef<dyn Parent>`. Unlike `Deferred<Wrc<T>>` it doesn't keep the cycle alive,
+/// so components are dropped with the container
+pub struct WeakRef<T: ?Sized> {
+    value: OnceLock<Weak<T>>,
+}
+
+impl<T: ?Sized> WeakRef<T> {
+    pub fn new() -> Self {
+        Self { value: OnceLock::new() }
+    }
+
+    pub fn init(&self, component: &Wrc<T>) -> Result<(), AlreadyInitialized<Weak<T>>> {
+        self.value.set(Wrc::downgrade(component)).map_err(AlreadyInitialized)
+    }
+
+    /// Returns `None` before the reference is injected or after the component is dropped
+    pub fn upgrade(&self) -> Option<Wrc<T>> {
+        self.value.get().and_then(Weak::upgrade)
+    }
+
+    pub fn get(&self) -> Wrc<T> {
+        self.upgrade().expect("WeakRef must be initialized and its component must be alive")
+    }
+}
+
+impl<T: ?Sized> Default for WeakRef<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Implemented for prototype components, a `WeakRef` to them would never be upgraded
+#[doc(hidden)]
+pub trait __WaiterPrototype {}
+
+/// `__WaiterWeakRefTarget::<T>::PROTOTYPE` is evaluated by `#[component]` for `WeakRef<T>` fields,
+/// the inherent constant shadows the `__WaiterNotPrototype` fallback only for prototypes
+#[doc(hidden)]
+pub struct __WaiterWeakRefTarget<T: ?Sized>(PhantomData<T>);
+
+impl<T: ?Sized + __WaiterPrototype> __WaiterWeakRefTarget<T> {
+    pub const PROTOTYPE: bool = true;
+}
+
+#[doc(hidden)]
+pub trait __WaiterNotPrototype {
+    const PROTOTYPE: bool = false;
+}
+
+impl<T: ?Sized> __WaiterNotPrototype for __WaiterWeakRefTarget<T> {}
+
+#[cfg(test)]
+mod tests {
+    use crate::{WeakRef, Wrc};
+
+    #[test]
+    fn upgrade_while_component_is_alive() {
+        let weak_ref = WeakRef::<str>::new();
+        assert_eq!(None, weak_ref.upgrade());
+
+        let component: Wrc<str> = Wrc::from("component");
+        weak_ref.init(&component).unwrap();
+        assert!(weak_ref.init(&component).is_err());
+        assert_eq!("component", &*weak_ref.get());
+
+        drop(component);
+        assert_eq!(None, weak_ref.upgrade());
+    }
+}
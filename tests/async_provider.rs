@@ -0,0 +1,188 @@
+#![cfg(feature = "async")]
+
+use std::future::Future;
+use std::pin::pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::task::{Context, Poll, Wake};
+use std::thread::{self, Thread};
+
+use waiter_di::*;
+
+static WARMED_UP: AtomicUsize = AtomicUsize::new(0);
+static SUBSCRIBED: AtomicBool = AtomicBool::new(false);
+static BROKER_DOWN: AtomicBool = AtomicBool::new(true);
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let waker = Arc::new(ThreadWaker(thread::current())).into();
+    let mut context = Context::from_waker(&waker);
+    loop {
+        match future.as_mut().poll(&mut context) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+/// Returns `Pending` once, like a future waiting for IO
+async fn yield_now() {
+    let mut yielded = false;
+    std::future::poll_fn(|context| {
+        if yielded {
+            return Poll::Ready(());
+        }
+        yielded = true;
+        context.waker().wake_by_ref();
+        Poll::Pending
+    }).await
+}
+
+struct Pool {
+    url: String,
+}
+
+#[provides]
+async fn pool(#[prop("str_prop")] url: String) -> Pool {
+    yield_now().await;
+    Pool { url }
+}
+
+trait Repository: ContainerSafe {
+    fn url(&self) -> &str;
+}
+
+#[component]
+struct UserRepository {
+    pool: Wrc<Pool>,
+}
+
+impl UserRepository {
+    #[post_construct]
+    async fn warm_up(&self) {
+        yield_now().await;
+        WARMED_UP.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[provides]
+impl Repository for UserRepository {
+    fn url(&self) -> &str {
+        &self.pool.url
+    }
+}
+
+#[component(scope = "prototype")]
+struct Handler {
+    repository: Wrc<dyn Repository>,
+    #[prop("int_v")] id: usize,
+}
+
+#[component]
+struct Gateway {
+    listener: Wrc<Listener>,
+}
+
+#[component]
+struct Listener {
+    gateway: Deferred<Wrc<Gateway>>,
+}
+
+impl Listener {
+    #[post_construct]
+    async fn subscribe(&self) {
+        yield_now().await;
+        SUBSCRIBED.store(self.gateway.try_get().is_some(), Ordering::SeqCst);
+    }
+}
+
+#[component]
+struct Consumer {
+    queue: Wrc<Queue>,
+    broker: Wrc<Broker>,
+}
+
+#[component]
+struct Queue {
+    consumer: Deferred<Wrc<Consumer>>,
+}
+
+#[component]
+struct Broker {}
+
+impl Broker {
+    #[post_construct]
+    async fn connect(&self) -> Result<(), String> {
+        yield_now().await;
+        if BROKER_DOWN.load(Ordering::SeqCst) {
+            Err("broker is down".to_string())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[test]
+fn async_factory_and_post_construct() {
+    let mut container = Container::<profiles::Default>::new();
+
+    let repository = block_on(AsyncProvider::<UserRepository>::get_async(&mut container));
+    assert_eq!(1, WARMED_UP.load(Ordering::SeqCst));
+    assert_eq!("str", repository.pool.url);
+
+    let handler = block_on(AsyncProvider::<Handler>::create_async(&mut container));
+    assert_eq!("str", handler.repository.url());
+    assert_eq!(3, handler.id);
+}
+
+#[test]
+fn async_singleton_is_resolved_synchronously_once_created() {
+    let mut container = Container::<profiles::Default>::new();
+
+    let error = Provider::<Pool>::try_get(&mut container).err().unwrap();
+    assert!(matches!(error, ResolveError::AsyncComponent { .. }));
+    assert!(error.to_string().contains("resolve it with get_async"));
+
+    let pool = block_on(AsyncProvider::<Pool>::get_async(&mut container));
+    assert!(Wrc::ptr_eq(&pool, &Provider::<Pool>::get(&mut container)));
+}
+
+#[test]
+fn futures_are_send() {
+    let mut container = Container::<profiles::Default>::new();
+
+    let handler = AsyncProvider::<Handler>::create_async(&mut container);
+    let handler = thread::scope(|scope| scope.spawn(move || block_on(handler)).join().unwrap());
+    assert_eq!("str", handler.repository.url());
+}
+
+#[test]
+fn post_construct_of_nested_singleton_after_deferred_injection() {
+    let mut container = Container::<profiles::Default>::new();
+
+    let gateway = block_on(AsyncProvider::<Gateway>::get_async(&mut container));
+    assert!(SUBSCRIBED.load(Ordering::SeqCst));
+    assert!(Wrc::ptr_eq(&gateway, &gateway.listener.gateway));
+}
+
+#[test]
+fn failed_post_construct_removes_pending_singletons() {
+    let mut container = Container::<profiles::Default>::new();
+
+    let error = block_on(AsyncProvider::<Consumer>::try_get_async(&mut container)).err().unwrap();
+    assert!(matches!(error, ResolveError::PostConstruct { .. }));
+    assert!(container.components().is_empty());
+
+    BROKER_DOWN.store(false, Ordering::SeqCst);
+    let consumer = block_on(AsyncProvider::<Consumer>::get_async(&mut container));
+    assert!(Wrc::ptr_eq(&consumer, &consumer.queue.consumer));
+    assert!(Wrc::ptr_eq(&consumer.broker, &Provider::<Broker>::get(&mut container)));
+}
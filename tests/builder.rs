@@ -0,0 +1,98 @@
+use config::Config;
+use waiter_di::*;
+
+const CONFIG_DIR: &str = "tests/config/service";
+
+#[component]
+struct Service {
+    #[prop("service.name")] name: String,
+    #[prop("service.port")] port: u16,
+}
+
+#[test]
+fn profile_files_from_config_dir() {
+    let mut container = Container::<profiles::Dev>::builder()
+        .config_dir(CONFIG_DIR)
+        .without_args()
+        .build();
+
+    let service = Provider::<Service>::get(&mut container);
+    assert_eq!("orders", service.name);
+    assert_eq!(9090, service.port);
+    assert!(container.config.get_string("str_prop").is_err());
+}
+
+#[test]
+fn later_sources_override_previous_ones() {
+    let container = Container::<profiles::Default>::builder()
+        .config_dir(CONFIG_DIR)
+        .sources(vec!(
+            ConfigSource::ProfileFiles,
+            ConfigSource::map([("service.port", 1)]),
+            ConfigSource::file(format!("{}/override.toml", CONFIG_DIR)),
+        ))
+        .build();
+    assert_eq!("billing", container.config.get_string("service.name").unwrap());
+    assert_eq!(1, container.config.get_int("service.port").unwrap());
+
+    let container = Container::<profiles::Default>::builder()
+        .config_dir(CONFIG_DIR)
+        .sources(vec!(ConfigSource::map([("service.port", 1)]), ConfigSource::ProfileFiles))
+        .build();
+    assert_eq!(8080, container.config.get_int("service.port").unwrap());
+}
+
+#[test]
+fn custom_source() {
+    let defaults = Config::builder()
+        .set_default("service.name", "custom").unwrap()
+        .set_default("service.port", 7).unwrap()
+        .build()
+        .unwrap();
+    let mut container = Container::<profiles::Default>::builder()
+        .sources(vec!(ConfigSource::custom(defaults)))
+        .build();
+
+    assert_eq!(7, Provider::<Service>::get(&mut container).port);
+}
+
+#[test]
+fn env_prefix_and_separator() {
+    std::env::set_var("WAITER_BUILDER_SERVICE__NAME", "from-env");
+    std::env::set_var("WAITER_BUILDER_SERVICE__PORT", "9000");
+    let mut container = Container::<profiles::Default>::builder()
+        .config_dir(CONFIG_DIR)
+        .env_prefix("WAITER_BUILDER")
+        .without_args()
+        .build();
+
+    let service = Provider::<Service>::get(&mut container);
+    assert_eq!("from-env", service.name);
+    assert_eq!(9000, service.port);
+    assert!(container.config.get_string("path").is_err());
+    assert_eq!(vec!(
+        EnvVar { name: "WAITER_BUILDER_SERVICE__NAME".to_string(), key: "service.name".to_string() },
+        EnvVar { name: "WAITER_BUILDER_SERVICE__PORT".to_string(), key: "service.port".to_string() },
+    ), container.env_vars());
+
+    let container = Container::<profiles::Default>::builder()
+        .config_dir(CONFIG_DIR)
+        .env_prefix("WAITER_BUILDER")
+        .env_separator("_")
+        .without_args()
+        .build();
+    // `SERVICE__NAME` has an empty key between separators, so it's skipped
+    assert!(container.env_vars().is_empty());
+    assert_eq!("orders", container.config.get_string("service.name").unwrap());
+}
+
+#[test]
+fn missing_required_file() {
+    let error = Container::<profiles::Default>::builder()
+        .add_source(ConfigSource::file(format!("{}/missing.toml", CONFIG_DIR)))
+        .without_args()
+        .try_build()
+        .err()
+        .unwrap();
+    assert!(matches!(error, ResolveError::ConfigLoad { .. }));
+}
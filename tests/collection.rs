@@ -0,0 +1,205 @@
+#![allow(dead_code)]
+
+use waiter_di::*;
+
+trait Handler: ContainerSafe {
+    fn name(&self) -> &'static str;
+}
+
+#[component]
+struct Auth;
+
+#[provides(order = 1)]
+impl Handler for Auth {
+    fn name(&self) -> &'static str { "auth" }
+}
+
+#[component]
+struct Logging;
+
+#[provides(order = -5)]
+impl Handler for Logging {
+    fn name(&self) -> &'static str { "logging" }
+}
+
+#[component]
+struct Router;
+
+#[provides]
+impl Handler for Router {
+    fn name(&self) -> &'static str { "router" }
+}
+
+#[component]
+struct DevTools;
+
+#[provides(profiles::Dev, order = 10)]
+impl Handler for DevTools {
+    fn name(&self) -> &'static str { "dev tools" }
+}
+
+trait Sink: ContainerSafe {
+    fn name(&self) -> &'static str;
+}
+
+#[component]
+struct FileSink;
+
+#[provides(order = 2)]
+impl Sink for FileSink {
+    fn name(&self) -> &'static str { "file" }
+}
+
+trait Format: ContainerSafe {
+    fn name(&self) -> &'static str;
+}
+
+#[component]
+struct Json;
+
+#[provides(order = 1)]
+impl Format for Json {
+    fn name(&self) -> &'static str { "json" }
+}
+
+#[component]
+struct Text;
+
+#[provides(order = 2)]
+impl Format for Text {
+    fn name(&self) -> &'static str { "text" }
+}
+
+trait Console: ContainerSafe {
+    fn name(&self) -> &'static str;
+}
+
+#[component]
+struct DevConsole;
+
+#[provides(profiles::Dev)]
+impl Console for DevConsole {
+    fn name(&self) -> &'static str { "dev console" }
+}
+
+#[component]
+struct Debugger {
+    console: Wrc<dyn Console>,
+}
+
+trait Tool: ContainerSafe {
+    fn console(&self) -> &'static str;
+}
+
+#[provides]
+impl Tool for Debugger {
+    fn console(&self) -> &'static str { self.console.name() }
+}
+
+#[component]
+struct Exporter {
+    sink: Wrc<dyn Sink>,
+    format: Wrc<dyn Format>,
+}
+
+trait Notifier: ContainerSafe {
+    fn name(&self) -> &'static str;
+}
+
+#[component]
+struct Email;
+
+#[provides]
+impl Notifier for Email {
+    fn name(&self) -> &'static str { "email" }
+}
+
+#[component]
+struct Sms;
+
+#[provides]
+impl Notifier for Sms {
+    fn name(&self) -> &'static str { "sms" }
+}
+
+#[component]
+struct Alerts {
+    notifier: Wrc<dyn Notifier>,
+}
+
+#[component]
+struct Pipeline {
+    handlers: Vec<Wrc<dyn Handler>>,
+    handlers_boxed: Vec<Box<dyn Handler>>,
+}
+
+fn names<T: std::ops::Deref<Target = dyn Handler>>(handlers: &[T]) -> Vec<&'static str> {
+    handlers.iter().map(|handler| handler.name()).collect()
+}
+
+#[test]
+fn inject_all_providers_in_order() {
+    let mut container = Container::<profiles::Default>::new();
+
+    let pipeline = Provider::<Pipeline>::get(&mut container);
+
+    assert_eq!(vec!("logging", "router", "auth"), names(&pipeline.handlers));
+    assert_eq!(vec!("logging", "router", "auth"), names(&pipeline.handlers_boxed));
+    assert_eq!("router", Provider::<dyn Handler>::get(&mut container).name());
+}
+
+#[test]
+fn single_provider_is_selected_by_order() {
+    let mut container = Container::<profiles::Default>::new();
+
+    let exporter = Provider::<Exporter>::get(&mut container);
+    assert_eq!("file", exporter.sink.name());
+    assert_eq!("json", exporter.format.name());
+    assert_eq!("file", container.try_get_selected::<dyn Sink>().unwrap().name());
+}
+
+#[test]
+fn profile_specific_providers() {
+    let mut container = Container::<profiles::Dev>::new();
+
+    assert_eq!(vec!("logging", "router", "auth", "dev tools"), names(&container.get_all::<dyn Handler>()));
+}
+
+#[test]
+fn dependencies_of_providers_use_container_profile() {
+    let mut container = Container::<profiles::Dev>::new();
+    assert_eq!("dev console", container.get_all::<dyn Tool>()[0].console());
+
+    let mut container = Container::<profiles::Default>::new();
+    assert!(matches!(container.try_get_all::<dyn Tool>(), Err(ResolveError::NoProvider { .. })));
+}
+
+#[test]
+fn singletons_are_shared() {
+    let mut container = Container::<profiles::Default>::new();
+
+    let router = Provider::<Router>::get(&mut container);
+    let handlers = container.get_all::<dyn Handler>();
+
+    assert!(std::ptr::eq(
+        Wrc::as_ptr(&router) as *const u8,
+        Wrc::as_ptr(&handlers[1]) as *const u8
+    ));
+}
+
+#[test]
+fn several_providers_without_order() {
+    let mut container = Container::<profiles::Default>::new();
+
+    let notifiers: Vec<&str> = container.get_all::<dyn Notifier>().iter().map(|notifier| notifier.name()).collect();
+    assert_eq!(vec!("email", "sms"), notifiers);
+
+    let errors = vec!(
+        Provider::<dyn Notifier>::try_get(&mut container).err().unwrap(),
+        container.try_get_selected::<dyn Notifier>().err().unwrap(),
+        Provider::<Alerts>::try_get(&mut container).err().unwrap(),
+    );
+    for error in errors {
+        assert!(matches!(error, ResolveError::AmbiguousProvider { candidates, .. } if candidates.len() == 2));
+    }
+}
@@ -0,0 +1,117 @@
+use waiter_di::*;
+
+trait Cache: ContainerSafe {
+    fn kind(&self) -> &'static str;
+}
+
+#[component]
+struct RedisCache;
+
+#[provides(when_prop("cache.kind" = "redis"))]
+impl Cache for RedisCache {
+    fn kind(&self) -> &'static str { "redis" }
+}
+
+#[component]
+struct FileCache;
+
+#[provides(when_prop = "cache.dir", when_prop("cache.kind" = "file"))]
+impl Cache for FileCache {
+    fn kind(&self) -> &'static str { "file" }
+}
+
+#[component]
+struct InMemoryCache;
+
+#[provides(when_missing = dyn Cache)]
+impl Cache for InMemoryCache {
+    fn kind(&self) -> &'static str { "in-memory" }
+}
+
+trait Metrics: ContainerSafe {
+    fn enabled(&self) -> bool;
+}
+
+#[component]
+struct PrometheusMetrics;
+
+#[provides(when_prop = "metrics.enabled")]
+impl Metrics for PrometheusMetrics {
+    fn enabled(&self) -> bool { true }
+}
+
+#[component]
+struct NoopMetrics;
+
+#[provides(when_missing = dyn Metrics)]
+impl Metrics for NoopMetrics {
+    fn enabled(&self) -> bool { false }
+}
+
+trait Exporter: ContainerSafe {
+    fn kind(&self) -> &'static str;
+}
+
+#[component]
+struct LogExporter;
+
+#[provides(when_missing = dyn Metrics)]
+impl Exporter for LogExporter {
+    fn kind(&self) -> &'static str { "log" }
+}
+
+#[component]
+struct NullExporter;
+
+#[provides(when_missing = dyn Exporter)]
+impl Exporter for NullExporter {
+    fn kind(&self) -> &'static str { "null" }
+}
+
+#[component]
+struct Service {
+    cache: Wrc<dyn Cache>,
+}
+
+fn container_with(props: &[(&str, &str)]) -> Container<profiles::Default> {
+    let mut container = Container::<profiles::Default>::new();
+    let mut config = config::Config::builder().add_source(container.config.clone());
+    for (key, value) in props {
+        config = config.set_override(*key, *value).unwrap();
+    }
+    container.config = config.build().unwrap();
+    container
+}
+
+#[test]
+fn select_by_property_value() {
+    let mut container = container_with(&[("cache.kind", "redis")]);
+    assert_eq!("redis", Provider::<Service>::get(&mut container).cache.kind());
+
+    let mut container = container_with(&[("cache.kind", "file"), ("cache.dir", "/tmp")]);
+    assert_eq!("file", Provider::<Service>::get(&mut container).cache.kind());
+}
+
+#[test]
+fn fallback_if_missing() {
+    let mut container = container_with(&[("cache.kind", "file")]);
+    assert_eq!("in-memory", Provider::<Service>::get(&mut container).cache.kind());
+}
+
+#[test]
+fn property_set_to_false() {
+    let mut container = container_with(&[("metrics.enabled", "false")]);
+    assert!(!container.try_get_selected::<dyn Metrics>().unwrap().enabled());
+
+    let mut container = container_with(&[("metrics.enabled", "true")]);
+    assert!(container.try_get_selected::<dyn Metrics>().unwrap().enabled());
+}
+
+#[test]
+fn fallback_conditions_are_evaluated() {
+    let mut container = container_with(&[]);
+    assert_eq!("log", container.try_get_selected::<dyn Exporter>().unwrap().kind());
+
+    let mut container = container_with(&[("metrics.enabled", "true")]);
+    assert_eq!("null", container.try_get_selected::<dyn Exporter>().unwrap().kind());
+}
@@ -0,0 +1,68 @@
+use config::Value;
+use serde::Deserialize;
+use waiter_di::*;
+
+#[config_properties(prefix = "database")]
+#[derive(Deserialize)]
+struct DatabaseConfig {
+    url: String,
+    pool_size: u32,
+}
+
+#[component]
+struct Repository {
+    config: Wrc<DatabaseConfig>,
+}
+
+#[derive(Deserialize)]
+struct CacheConfig {
+    ttl: u64,
+}
+
+#[component]
+struct Cache {
+    #[prop("cache")] config: CacheConfig,
+    #[prop("backup")] backup: Option<CacheConfig>,
+}
+
+fn container() -> Container<profiles::Default> {
+    Container::<profiles::Default>::builder()
+        .sources(vec!(ConfigSource::map([
+            ("database.url", Value::from("postgres://localhost")),
+            ("database.pool_size", Value::from(4)),
+            ("cache.ttl", Value::from(60)),
+        ])))
+        .build()
+}
+
+#[test]
+fn section_is_injected_as_singleton() {
+    let mut container = container();
+    let repository = Provider::<Repository>::get(&mut container);
+    assert_eq!("postgres://localhost", repository.config.url);
+    assert_eq!(4, repository.config.pool_size);
+
+    let config = Provider::<DatabaseConfig>::get(&mut container);
+    assert!(Wrc::ptr_eq(&config, &repository.config));
+    assert!(container.validate::<Repository>().is_ok());
+}
+
+#[test]
+fn prop_reads_struct_section() {
+    let mut container = container();
+    let cache = Provider::<Cache>::get(&mut container);
+    assert_eq!(60, cache.config.ttl);
+    assert!(cache.backup.is_none());
+}
+
+#[test]
+fn missing_section() {
+    let mut container = Container::<profiles::Default>::builder()
+        .sources(vec!(ConfigSource::map([("cache.ttl", 60)])))
+        .build();
+
+    let error = Provider::<Repository>::try_get(&mut container).err().unwrap();
+    assert!(format!("{}", error).contains("database"), "{}", error);
+    let problems = container.validate::<Repository>().err().unwrap().problems;
+    assert!(matches!(problems[..], [ResolveError::MissingProperty { .. }]), "{:?}", problems);
+}
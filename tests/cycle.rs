@@ -0,0 +1,91 @@
+#![allow(dead_code)]
+
+use waiter_di::*;
+
+#[component]
+struct A {
+    b: Wrc<B>,
+}
+
+#[component]
+struct B {
+    c: Box<C>,
+}
+
+#[component]
+struct C {
+    a: Wrc<A>,
+}
+
+#[component]
+struct Root {
+    a: Wrc<A>,
+}
+
+#[component]
+struct Parent {
+    child: Wrc<Child>,
+}
+
+#[component]
+struct Child {
+    parent: Deferred<Wrc<Parent>>,
+}
+
+#[test]
+fn cycle_is_reported() {
+    let mut container = Container::<profiles::Default>::new();
+    let error = Provider::<Root>::try_get(&mut container).err().unwrap();
+
+    match &error {
+        ResolveError::DependencyCycle { cycle, .. } => assert_eq!(
+            &vec!("cycle::A", "cycle::B", "cycle::C", "cycle::A"),
+            cycle
+        ),
+        other => panic!("Unexpected error: {}", other)
+    }
+    assert!(error.to_string().starts_with(
+        "Dependency cycle cycle::A -> cycle::B -> cycle::C -> cycle::A, inject one of them as Deferred"
+    ));
+    assert_eq!(vec!("cycle::Root", "cycle::A", "cycle::B", "cycle::C", "cycle::A"), error.path());
+}
+
+#[test]
+fn container_is_usable_after_cycle() {
+    let mut container = Container::<profiles::Default>::new();
+    assert!(Provider::<A>::try_get(&mut container).is_err());
+
+    let parent = Provider::<Parent>::get(&mut container);
+    assert!(Wrc::ptr_eq(&parent, &parent.child.parent));
+}
+
+#[component]
+struct Broken {
+    #[prop("absent.property")] value: String,
+}
+
+#[component]
+struct Service {
+    child: Wrc<Child>,
+    broken: Wrc<Broken>,
+}
+
+#[test]
+fn not_initialized_components_are_removed_after_failure() {
+    let mut container = Container::<profiles::Default>::new();
+    assert!(Provider::<Service>::try_get(&mut container).is_err());
+    assert!(container.components().is_empty());
+
+    let child = Provider::<Child>::get(&mut container);
+    assert!(Wrc::ptr_eq(&Provider::<Parent>::get(&mut container), &child.parent));
+}
+
+#[test]
+fn deferred_is_injected_once() {
+    let mut container = Container::<profiles::Default>::new();
+    let child = Provider::<Child>::get(&mut container);
+
+    let error = Component::__waiter_inject_deferred(&mut container, &*child).err().unwrap();
+    assert!(matches!(error, ResolveError::AlreadyInitialized { .. }), "{}", error);
+    assert!(error.to_string().contains("is already initialized"));
+}
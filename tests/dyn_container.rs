@@ -0,0 +1,79 @@
+use waiter_di::*;
+
+trait Greeter {
+    fn greet(&self) -> String;
+}
+
+#[component]
+struct DefaultGreeter;
+
+#[provides(profiles::Default)]
+impl Greeter for DefaultGreeter {
+    fn greet(&self) -> String { "hello".to_string() }
+}
+
+#[component]
+struct DevGreeter;
+
+#[provides(profiles::Dev)]
+impl Greeter for DevGreeter {
+    fn greet(&self) -> String { "hello, developer".to_string() }
+}
+
+#[component]
+struct Counter {
+    #[prop("int_v")] value: i64,
+}
+
+/// Provider written by hand, it implements only `try_get` and `try_create`
+struct Clock(u64);
+
+impl<P> Provider<Clock> for Container<P> {
+    type Impl = Clock;
+    fn try_get(&mut self) -> Result<Wrc<Clock>, ResolveError> {
+        Ok(Wrc::new(Clock(42)))
+    }
+    fn try_create(&mut self) -> Result<Clock, ResolveError> {
+        Ok(Clock(42))
+    }
+}
+
+#[component]
+struct Scheduler {
+    clock: Wrc<Clock>,
+}
+
+type Profiles = (profiles::Default, profiles::Dev);
+
+#[test]
+fn resolve_for_runtime_profile() {
+    let mut container = DynContainer::<Profiles>::try_with_profile("dev").unwrap();
+
+    assert_eq!("hello, developer", container.get::<dyn Greeter>().greet());
+    assert_eq!("hello, developer", container.create::<dyn Greeter>().greet());
+    assert_eq!(74, container.get::<Counter>().value);
+    assert_eq!(1, container.get_all::<dyn Greeter>().len());
+
+    let mut container = DynContainer::<Profiles>::try_with_profile("default").unwrap();
+
+    assert_eq!("hello", container.get::<dyn Greeter>().greet());
+    assert_eq!(3, container.get::<Counter>().value);
+}
+
+#[test]
+fn singletons_are_shared_between_calls() {
+    let mut container = DynContainer::<Profiles>::try_with_profile("dev").unwrap();
+
+    let first = container.get::<Counter>();
+    let second = container.get::<Counter>();
+
+    assert!(Wrc::ptr_eq(&first, &second));
+}
+
+#[test]
+fn hand_written_provider() {
+    let mut container = DynContainer::<Profiles>::try_with_profile("dev").unwrap();
+
+    assert_eq!(42, container.get::<Clock>().0);
+    assert_eq!(42, container.get::<Scheduler>().clock.0);
+}
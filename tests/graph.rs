@@ -0,0 +1,66 @@
+#![allow(dead_code)]
+
+use config::Config;
+use waiter_di::*;
+
+trait Repository: ContainerSafe {}
+
+#[component]
+struct PostgresRepository {
+    #[prop("db.url")] url: String,
+}
+
+#[provides]
+impl Repository for PostgresRepository {}
+
+#[component]
+struct Cache;
+
+#[component]
+struct Service {
+    repository: Wrc<dyn Repository>,
+    cache: Deferred<Wrc<Cache>>,
+    listeners: Vec<Box<dyn Repository>>,
+}
+
+struct Clock;
+
+#[provides]
+fn clock(config: Config) -> Clock {
+    let _ = config;
+    Clock
+}
+
+#[module]
+struct RootModule {
+    service: Wrc<Service>,
+    clock: Clock,
+}
+
+#[test]
+fn graph_of_module() {
+    let container = Container::<profiles::Default>::new();
+    let graph = container.dependency_graph().reachable_from("graph::RootModule");
+
+    let edges: Vec<(&str, &str, &str, DependencyKind)> = graph.edges.iter()
+        .map(|edge| (edge.from.as_str(), edge.to.as_str(), edge.field.as_str(), edge.kind))
+        .collect();
+    assert_eq!(vec!(
+        ("dyn graph::Repository", "graph::PostgresRepository", "graph::PostgresRepository", DependencyKind::Implementation),
+        ("graph::Clock", "config", "config", DependencyKind::Config),
+        ("graph::PostgresRepository", "db.url", "url", DependencyKind::Prop),
+        ("graph::RootModule", "graph::Clock", "clock", DependencyKind::Value),
+        ("graph::RootModule", "graph::Service", "service", DependencyKind::Wrc),
+        ("graph::Service", "dyn graph::Repository", "listeners", DependencyKind::Vec),
+        ("graph::Service", "dyn graph::Repository", "repository", DependencyKind::Wrc),
+        ("graph::Service", "graph::Cache", "cache", DependencyKind::Deferred),
+    ), edges);
+
+    let dot = graph.to_dot();
+    assert!(dot.contains("\"graph::Service\" -> \"graph::Cache\" [label=\"cache: deferred\", style=dashed];"));
+    let json = graph.to_json();
+    assert!(json.contains(
+        "{\"from\": \"graph::Service\", \"to\": \"dyn graph::Repository\", \"field\": \"repository\", \
+        \"type\": \"Wrc<dyn Repository>\", \"kind\": \"wrc\", \"qualifier\": null}"
+    ));
+}
@@ -0,0 +1,161 @@
+#![allow(dead_code)]
+
+use std::cell::Cell;
+
+use waiter_di::*;
+
+thread_local! {
+    static CLIENTS_CREATED: Cell<usize> = const { Cell::new(0) };
+    static JOBS_CREATED: Cell<usize> = const { Cell::new(0) };
+    static CONNECTIONS_CLOSED: Cell<usize> = const { Cell::new(0) };
+}
+
+struct ExpensiveClient {
+    url: String,
+}
+
+#[provides]
+fn expensive_client(#[prop("str_prop")] url: String) -> ExpensiveClient {
+    CLIENTS_CREATED.with(|created| created.set(created.get() + 1));
+    ExpensiveClient { url }
+}
+
+trait Job {
+    fn id(&self) -> usize;
+}
+
+#[component(scope = "prototype")]
+struct ImportJob {
+    #[prop("int_v")] id: usize,
+}
+
+impl ImportJob {
+    #[post_construct]
+    fn created(&self) {
+        JOBS_CREATED.with(|created| created.set(created.get() + 1));
+    }
+}
+
+#[provides]
+impl Job for ImportJob {
+    fn id(&self) -> usize {
+        self.id
+    }
+}
+
+#[component]
+struct Scheduler {
+    client: Lazy<Wrc<ExpensiveClient>>,
+    jobs: Factory<ImportJob>,
+    boxed_jobs: Factory<Box<dyn Job>>,
+}
+
+trait Notifier: ContainerSafe {
+    fn channel(&self) -> &'static str;
+}
+
+#[component]
+struct ConsoleNotifier;
+
+#[provides(profiles::Dev)]
+impl Notifier for ConsoleNotifier {
+    fn channel(&self) -> &'static str {
+        "console"
+    }
+}
+
+#[component]
+struct Connection;
+
+impl Connection {
+    #[pre_destroy]
+    fn close(&self) {
+        CONNECTIONS_CLOSED.with(|closed| closed.set(closed.get() + 1));
+    }
+}
+
+#[component]
+struct Reporter {
+    notifier: Lazy<Wrc<dyn Notifier>>,
+    connection: Lazy<Wrc<Connection>>,
+}
+
+#[test]
+fn lazy_is_resolved_on_first_deref() {
+    let mut container = Container::<profiles::Default>::new();
+    let scheduler = Provider::<Scheduler>::get(&mut container);
+    assert_eq!(0, CLIENTS_CREATED.with(Cell::get));
+    assert!(!scheduler.client.is_resolved());
+
+    assert_eq!("str", scheduler.client.url);
+    assert_eq!("str", scheduler.client.url);
+    assert_eq!(1, CLIENTS_CREATED.with(Cell::get));
+}
+
+#[test]
+fn factory_creates_new_instances() {
+    let mut container = Container::<profiles::Default>::new();
+    let scheduler = Provider::<Scheduler>::get(&mut container);
+
+    assert_eq!(0, JOBS_CREATED.with(Cell::get));
+    assert_eq!(3, scheduler.jobs.create().id);
+    assert_eq!(3, scheduler.jobs.create().id);
+    assert_eq!(3, scheduler.boxed_jobs.create().id());
+    assert_eq!(3, JOBS_CREATED.with(Cell::get));
+}
+
+#[test]
+fn lazy_and_factory_in_graph() {
+    let container = Container::<profiles::Default>::new();
+    let graph = container.dependency_graph().reachable_from("lazy::Scheduler");
+
+    let edges: Vec<(&str, &str, DependencyKind)> = graph.edges.iter()
+        .filter(|edge| edge.from == "lazy::Scheduler")
+        .map(|edge| (edge.field.as_str(), edge.to.as_str(), edge.kind))
+        .collect();
+    assert_eq!(vec!(
+        ("boxed_jobs", "dyn lazy::Job", DependencyKind::Factory),
+        ("client", "lazy::ExpensiveClient", DependencyKind::Lazy),
+        ("jobs", "lazy::ImportJob", DependencyKind::Factory),
+    ), edges);
+}
+
+#[test]
+fn lazy_shares_singletons_with_container() {
+    let mut container = Container::<profiles::Default>::new();
+    let scheduler = Provider::<Scheduler>::get(&mut container);
+
+    let client = Provider::<ExpensiveClient>::get(&mut container);
+    assert!(Wrc::ptr_eq(&client, &scheduler.client));
+    assert_eq!(1, CLIENTS_CREATED.with(Cell::get));
+}
+
+#[test]
+fn lazy_uses_container_profile() {
+    let mut container = Container::<profiles::Dev>::new();
+    let reporter = Provider::<Reporter>::get(&mut container);
+
+    assert_eq!("console", reporter.notifier.channel());
+}
+
+#[test]
+fn lazy_singletons_are_destroyed_with_container() {
+    let mut container = Container::<profiles::Default>::new();
+    let reporter = Provider::<Reporter>::get(&mut container);
+    let _ = &*reporter.connection;
+
+    container.close();
+    assert_eq!(1, CONNECTIONS_CLOSED.with(Cell::get));
+    drop(reporter);
+    assert_eq!(1, CONNECTIONS_CLOSED.with(Cell::get));
+}
+
+#[test]
+fn lazy_is_not_resolved_after_close() {
+    let mut container = Container::<profiles::Default>::new();
+    let reporter = Provider::<Reporter>::get(&mut container);
+    container.close();
+
+    let error = reporter.connection.try_get().err().unwrap();
+    assert!(matches!(error, ResolveError::ContainerClosed { .. }));
+}
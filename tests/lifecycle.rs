@@ -0,0 +1,151 @@
+use std::cell::RefCell;
+use std::error::Error;
+
+use waiter_di::*;
+
+thread_local! {
+    static EVENTS: RefCell<Vec<String>> = const { RefCell::new(vec!()) };
+}
+
+fn event(event: &str) {
+    EVENTS.with(|events| events.borrow_mut().push(event.to_string()));
+}
+
+fn events() -> Vec<String> {
+    EVENTS.with(|events| events.borrow_mut().drain(..).collect())
+}
+
+#[component]
+struct Pool;
+
+impl Pool {
+    #[post_construct]
+    fn connect(&self) {
+        event("Pool connected");
+    }
+
+    #[pre_destroy]
+    fn disconnect(&self) {
+        event("Pool disconnected");
+    }
+}
+
+#[component]
+struct Repository {
+    pool: Wrc<Pool>,
+    cache: Deferred<Wrc<Cache>>,
+}
+
+impl Repository {
+    #[post_construct]
+    fn warm_up(&self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let _ = &self.pool;
+        event(&format!("Repository warmed up with {}", self.cache.0));
+        Ok(())
+    }
+
+    #[pre_destroy]
+    fn flush(&self) {
+        event("Repository flushed");
+    }
+}
+
+struct Cache(usize);
+
+#[provides]
+fn cache() -> Cache {
+    Cache(16)
+}
+
+struct Service;
+
+#[provides]
+fn service(_pool: Wrc<Pool>) -> Service {
+    event("Service created");
+    Service
+}
+
+#[component]
+struct Holder {
+    service: Wrc<Service>,
+}
+
+#[component]
+struct Broken;
+
+impl Broken {
+    #[post_construct]
+    fn check(&self) -> Result<(), String> {
+        Err("not ready".to_string())
+    }
+}
+
+#[test]
+fn post_construct_after_deferred_injection() {
+    let mut container = Container::<profiles::Default>::new();
+    Provider::<Repository>::get(&mut container);
+    Provider::<Repository>::get(&mut container);
+    assert_eq!(vec!("Pool connected", "Repository warmed up with 16"), events());
+
+    Provider::<Repository>::create(&mut container);
+    assert_eq!(vec!("Repository warmed up with 16"), events());
+}
+
+#[test]
+fn post_construct_before_injection() {
+    let mut container = Container::<profiles::Default>::new();
+    let holder = Provider::<Holder>::get(&mut container);
+    assert_eq!(vec!("Pool connected", "Service created"), events());
+    assert!(Wrc::ptr_eq(&holder.service, &Provider::<Service>::get(&mut container)));
+}
+
+#[test]
+fn pre_destroy_in_reverse_dependency_order() {
+    let mut container = Container::<profiles::Default>::new();
+    Provider::<Repository>::get(&mut container);
+    events();
+
+    assert!(container.close().is_empty());
+    assert_eq!(vec!("Repository flushed", "Pool disconnected"), events());
+}
+
+#[test]
+fn close_on_drop() {
+    {
+        let mut container = Container::<profiles::Default>::new();
+        Provider::<Repository>::get(&mut container);
+        events();
+    }
+    assert_eq!(vec!("Repository flushed", "Pool disconnected"), events());
+}
+
+#[test]
+fn report_components_referenced_after_close() {
+    let mut container = Container::<profiles::Default>::new();
+    let pool = Provider::<Pool>::get(&mut container);
+
+    let leaked = container.close();
+    assert_eq!(vec!(LeakedComponent { type_name: "lifecycle::Pool", strong_count: 1 }), leaked);
+    assert!(container.is_closed());
+    drop(pool);
+}
+
+#[test]
+fn refuse_resolution_after_close() {
+    let mut container = Container::<profiles::Default>::new();
+    container.close();
+
+    let error = Provider::<Pool>::try_get(&mut container).err().unwrap();
+    assert!(matches!(error, ResolveError::ContainerClosed { .. }));
+    assert!(Provider::<Pool>::try_create(&mut container).is_err());
+}
+
+#[test]
+fn failed_post_construct() {
+    let mut container = Container::<profiles::Default>::new();
+    let error = Provider::<Broken>::try_get(&mut container).err().unwrap();
+
+    assert!(matches!(error, ResolveError::PostConstruct { .. }));
+    assert_eq!("Post construct failed: not ready (while resolving lifecycle::Broken)", error.to_string());
+    assert!(container.components().is_empty());
+}
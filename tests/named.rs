@@ -0,0 +1,62 @@
+use waiter_di::*;
+
+trait Storage: ContainerSafe {
+    fn name(&self) -> &'static str;
+}
+
+#[component]
+struct PrimaryStorage;
+
+#[provides]
+impl Storage for PrimaryStorage {
+    fn name(&self) -> &'static str { "primary" }
+}
+
+#[component]
+struct ReplicaStorage;
+
+#[provides(name = "replica")]
+impl Storage for ReplicaStorage {
+    fn name(&self) -> &'static str { "replica" }
+}
+
+#[component]
+struct DevReplicaStorage;
+
+#[provides(profiles::Dev, name = "dev-replica")]
+impl Storage for DevReplicaStorage {
+    fn name(&self) -> &'static str { "dev replica" }
+}
+
+#[component]
+struct Service {
+    primary: Wrc<dyn Storage>,
+    #[inject("replica")] replica: Wrc<dyn Storage>,
+    #[inject("replica")] replica_boxed: Box<dyn Storage>,
+}
+
+struct DevService(Wrc<dyn Storage>);
+
+#[provides(profiles::Dev)]
+fn create_dev_service(#[inject("dev-replica")] storage: Wrc<dyn Storage>) -> DevService {
+    DevService(storage)
+}
+
+#[test]
+fn inject_named_components() {
+    let mut container = Container::<profiles::Default>::new();
+
+    let service = Provider::<Service>::get(&mut container);
+
+    assert_eq!("primary", service.primary.name());
+    assert_eq!("replica", service.replica.name());
+    assert_eq!("replica", service.replica_boxed.name());
+    assert_eq!(2, container.get_all::<dyn Storage>().len());
+}
+
+#[test]
+fn inject_named_components_for_profile() {
+    let mut container = Container::<profiles::Dev>::new();
+
+    assert_eq!("dev replica", Provider::<DevService>::get(&mut container).0.name());
+}
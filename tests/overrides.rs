@@ -0,0 +1,112 @@
+use std::sync::Mutex;
+
+use waiter_di::*;
+
+trait Mailer: ContainerSafe {
+    fn send(&self, to: &str);
+    fn sent(&self) -> Vec<String>;
+}
+
+#[component]
+struct SmtpMailer;
+
+#[provides]
+impl Mailer for SmtpMailer {
+    fn send(&self, _to: &str) {
+        panic!("Real mailer must not be used in tests")
+    }
+    fn sent(&self) -> Vec<String> {
+        vec!()
+    }
+}
+
+#[derive(Default)]
+struct FakeMailer {
+    sent: Mutex<Vec<String>>,
+}
+
+impl Mailer for FakeMailer {
+    fn send(&self, to: &str) {
+        self.sent.lock().unwrap().push(to.to_string());
+    }
+    fn sent(&self) -> Vec<String> {
+        self.sent.lock().unwrap().clone()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Clock(i64);
+
+#[provides]
+fn system_clock() -> Clock {
+    Clock(1_700_000_000)
+}
+
+#[component]
+struct OrderService {
+    mailer: Wrc<dyn Mailer>,
+    notifications: Box<dyn Mailer>,
+    clock: Clock,
+}
+
+impl OrderService {
+    fn place_order(&self, customer: &str) {
+        self.mailer.send(customer);
+    }
+}
+
+#[test]
+fn real_service_with_fake_dependencies() {
+    let mut container = Container::<profiles::Default>::new();
+    let mailer = Wrc::new(FakeMailer::default());
+    container.override_with::<dyn Mailer>(mailer.clone());
+    container.override_create_with::<dyn Mailer, _>(|| Box::new(FakeMailer::default()));
+    container.override_create_with::<Clock, _>(|| Box::new(Clock(42)));
+
+    let service = Provider::<OrderService>::get(&mut container);
+    service.place_order("john@example.com");
+
+    assert_eq!(vec!("john@example.com"), mailer.sent());
+    assert!(service.notifications.sent().is_empty());
+    assert_eq!(Clock(42), service.clock);
+}
+
+#[test]
+fn override_concrete_component() {
+    let mut container = Container::<profiles::Default>::new();
+    container.override_with(Wrc::new(Clock(7)));
+
+    assert_eq!(Clock(7), *Provider::<Clock>::get(&mut container));
+    assert_eq!(Clock(1_700_000_000), Provider::<Clock>::create(&mut container));
+}
+
+#[test]
+fn override_replaces_all_implementations() {
+    let mut container = Container::<profiles::Default>::new();
+    let mailer = Wrc::new(FakeMailer::default());
+    container.override_with::<dyn Mailer>(mailer.clone());
+
+    let mailers = container.get_all::<dyn Mailer>();
+    assert_eq!(1, mailers.len());
+    mailers[0].send("john@example.com");
+    assert_eq!(vec!("john@example.com"), mailer.sent());
+}
+
+#[test]
+fn overridden_trait_is_not_resolved_as_implementation() {
+    let mut container = Container::<profiles::Default>::new();
+    container.override_with::<dyn Mailer>(Wrc::new(FakeMailer::default()));
+
+    let error = Provider::<dyn Mailer>::try_get(&mut container).err().unwrap();
+    assert!(matches!(error, ResolveError::Overridden { .. }));
+}
+
+#[test]
+fn override_in_dyn_container() {
+    let mut container = DynContainer::<(profiles::Default, profiles::Dev)>::try_with_profile("dev").unwrap();
+    let mailer = Wrc::new(FakeMailer::default());
+    container.override_with::<dyn Mailer>(mailer.clone());
+
+    container.get::<dyn Mailer>().send("john@example.com");
+    assert_eq!(vec!("john@example.com"), mailer.sent());
+}
@@ -0,0 +1,80 @@
+use waiter_di::*;
+
+trait Database: ContainerSafe {
+    fn name(&self) -> &'static str;
+}
+
+#[component]
+struct PostgresDatabase;
+
+#[provides(profile = "prod | dev & !mock-db")]
+impl Database for PostgresDatabase {
+    fn name(&self) -> &'static str { "postgres" }
+}
+
+#[component]
+struct MockDatabase;
+
+#[provides(profile = "mock-db")]
+impl Database for MockDatabase {
+    fn name(&self) -> &'static str { "mock" }
+}
+
+#[component]
+struct Repository {
+    database: Wrc<dyn Database>,
+    database_boxed: Box<dyn Database>,
+}
+
+struct Connection(&'static str);
+
+#[provides(profiles::Dev)]
+fn dev_connection() -> Connection {
+    Connection("dev")
+}
+
+#[component]
+struct Session {
+    connection: Wrc<Connection>,
+}
+
+#[test]
+fn provider_selected_by_profile_expression() {
+    let mut container = Container::<profiles::Dev>::new();
+    assert_eq!("postgres", Provider::<Repository>::get(&mut container).database.name());
+
+    let mut container = Container::<profiles::Dev>::with_profiles(&["mock-db"]);
+    let repository = Provider::<Repository>::get(&mut container);
+    assert_eq!("mock", repository.database.name());
+    assert_eq!("mock", repository.database_boxed.name());
+}
+
+#[test]
+fn no_or_several_active_providers() {
+    let mut container = Container::<profiles::Default>::new();
+    let error = Provider::<Repository>::try_get(&mut container).err().unwrap();
+    assert!(matches!(error, ResolveError::NoProvider { .. }));
+
+    let mut container = Container::<profiles::Default>::with_profiles(&["prod", "mock-db"]);
+    let error = Provider::<Repository>::try_get(&mut container).err().unwrap();
+    assert!(matches!(error, ResolveError::AmbiguousProvider { .. }));
+}
+
+#[test]
+fn profile_specific_factory_injected() {
+    let mut container = Container::<profiles::Dev>::new();
+    assert_eq!("dev", Provider::<Session>::get(&mut container).connection.0);
+
+    let mut container = Container::<profiles::Default>::new();
+    let error = Provider::<Session>::try_get(&mut container).err().unwrap();
+    assert!(matches!(error, ResolveError::NoProvider { .. }));
+}
+
+#[test]
+fn profile_configs_are_stacked() {
+    let container = Container::<profiles::Default>::with_profiles(&["dev", "eu-region"]);
+
+    assert_eq!(&["default", "dev", "eu-region"], container.active_profiles());
+    assert_eq!(74, container.config.get_int("int_v").unwrap());
+    assert_eq!(7, container.config.get_int("i32_prop").unwrap());
+}
@@ -0,0 +1,124 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use waiter_di::*;
+
+#[component]
+struct RateLimiter {
+    #[prop("limits.rps")] rps: Reloadable<u32>,
+    #[prop("log.level")] level: Reloadable<String>,
+    #[prop("limits.burst")] burst: u32,
+}
+
+#[component(scope = "prototype")]
+struct Throttle {
+    #[prop("throttle.delay")] delay: Reloadable<u32>,
+}
+
+fn config_dir(test: &str, content: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("waiter_reload_{}_{}", test, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("default.toml"), content).unwrap();
+    dir
+}
+
+fn container(dir: &PathBuf) -> Container<profiles::Default> {
+    Container::<profiles::Default>::builder()
+        .config_dir(dir)
+        .without_env()
+        .without_args()
+        .build()
+}
+
+const CONFIG: &str = "log.level = \"info\"\nlimits.rps = 10\nlimits.burst = 5\nthrottle.delay = 1\n";
+
+#[test]
+fn reloadable_properties_follow_config() {
+    let dir = config_dir("follow", CONFIG);
+    let mut container = container(&dir);
+    let limiter = Provider::<RateLimiter>::get(&mut container);
+    assert_eq!(10, *limiter.rps.get());
+
+    let notified = Arc::new(AtomicUsize::new(0));
+    let listener_notified = notified.clone();
+    container.on_config_change("limits", move |config| {
+        assert_eq!(20, config.get_int("limits.rps").unwrap());
+        listener_notified.fetch_add(1, Ordering::SeqCst);
+    });
+    container.on_config_change("log", |_| panic!("log.level isn't changed"));
+
+    let changed = CONFIG.replace("rps = 10", "rps = 20").replace("burst = 5", "burst = 6");
+    fs::write(dir.join("default.toml"), changed).unwrap();
+    assert_eq!(vec!("limits".to_string()), container.reload_config().unwrap());
+
+    assert_eq!(20, *limiter.rps.get());
+    assert_eq!("info", *limiter.level.get());
+    assert_eq!(5, limiter.burst);
+    assert_eq!(6, container.config.get_int("limits.burst").unwrap());
+    assert_eq!(1, notified.load(Ordering::SeqCst));
+}
+
+#[test]
+fn invalid_reload_is_rejected() {
+    let dir = config_dir("invalid", CONFIG);
+    let mut container = container(&dir);
+    let limiter = Provider::<RateLimiter>::get(&mut container);
+
+    fs::write(dir.join("default.toml"), CONFIG.replace("rps = 10", "rps = \"many\"")).unwrap();
+    let error = container.reload_config().err().unwrap();
+    assert!(matches!(error, ResolveError::InvalidProperty { .. }), "{}", error);
+
+    assert_eq!(10, *limiter.rps.get());
+    assert_eq!(10, container.config.get_int("limits.rps").unwrap());
+}
+
+#[test]
+fn watch_detects_changed_files() {
+    let dir = config_dir("watch", CONFIG);
+    let container = container(&dir);
+    let mut watch = container.config_watch();
+    assert!(!watch.changed());
+
+    fs::write(dir.join("default.toml"), CONFIG.replace("info", "debug")).unwrap();
+    assert!(watch.changed());
+    assert!(!watch.changed());
+
+    fs::write(dir.join("default.json"), "{}").unwrap();
+    assert!(watch.changed());
+}
+
+#[test]
+fn reload_from_background_thread() {
+    let dir = config_dir("background", CONFIG);
+    let mut container = container(&dir);
+    let limiter = Provider::<RateLimiter>::get(&mut container);
+
+    let reloader = container.config_reloader();
+    let mut watch = reloader.watch();
+    let changed = CONFIG.replace("rps = 10", "rps = 20").replace("delay = 1", "delay = 2");
+    fs::write(dir.join("default.toml"), changed).unwrap();
+    let reloaded = std::thread::spawn(move || reloader.reload_if_changed(&mut watch))
+        .join()
+        .unwrap();
+    assert_eq!(Some(vec!()), reloaded.unwrap());
+
+    assert_eq!(20, *limiter.rps.get());
+    assert_eq!(2, *Provider::<Throttle>::create(&mut container).delay.get());
+}
+
+#[test]
+fn dropped_reloadable_properties_are_not_checked() {
+    let dir = config_dir("dropped", CONFIG);
+    let mut container = container(&dir);
+    let throttle = Provider::<Throttle>::create(&mut container);
+    assert_eq!(1, *throttle.delay.get());
+
+    fs::write(dir.join("default.toml"), CONFIG.replace("delay = 1", "delay = \"slow\"")).unwrap();
+    assert!(container.reload_config().is_err());
+
+    drop(throttle);
+    assert!(container.reload_config().is_ok());
+}
@@ -0,0 +1,137 @@
+#![allow(dead_code)]
+
+use std::cell::RefCell;
+
+use waiter_di::*;
+
+thread_local! {
+    static EVENTS: RefCell<Vec<String>> = const { RefCell::new(vec!()) };
+}
+
+fn event(event: &str) {
+    EVENTS.with(|events| events.borrow_mut().push(event.to_string()));
+}
+
+fn events() -> Vec<String> {
+    EVENTS.with(|events| events.borrow_mut().drain(..).collect())
+}
+
+#[component]
+struct Database;
+
+#[component(scope = "request")]
+struct Transaction {
+    database: Wrc<Database>,
+}
+
+impl Transaction {
+    #[pre_destroy]
+    fn commit(&self) {
+        event("Transaction committed");
+    }
+}
+
+#[component(scope = "request")]
+struct OrderHandler {
+    transaction: Wrc<Transaction>,
+    database: Wrc<Database>,
+}
+
+impl OrderHandler {
+    #[pre_destroy]
+    fn finish(&self) {
+        event("Order handled");
+    }
+}
+
+#[component]
+struct Auditor {
+    transaction: Wrc<Transaction>,
+}
+
+#[component]
+struct Reporter {
+    auditor: Wrc<Auditor>,
+}
+
+#[component]
+struct Exporter {
+    transaction: Factory<Transaction>,
+}
+
+#[test]
+fn request_components_are_cached_in_child() {
+    let mut container = Container::<profiles::Default>::new();
+    let database = Provider::<Database>::get(&mut container);
+
+    let mut request = container.child();
+    let handler = Provider::<OrderHandler>::get(&mut *request);
+    assert!(Wrc::ptr_eq(&handler, &Provider::<OrderHandler>::get(&mut *request)));
+    assert!(Wrc::ptr_eq(&handler.transaction, &Provider::<Transaction>::get(&mut *request)));
+    assert!(Wrc::ptr_eq(&database, &handler.database));
+}
+
+#[test]
+fn request_components_are_destroyed_with_child() {
+    let container = Container::<profiles::Default>::new();
+
+    let first = {
+        let mut request = container.child();
+        let transaction = Provider::<Transaction>::get(&mut *request);
+        Provider::<OrderHandler>::get(&mut *request);
+        transaction
+    };
+    assert_eq!(vec!("Order handled", "Transaction committed"), events());
+
+    let mut request = container.child();
+    assert!(!Wrc::ptr_eq(&first, &Provider::<Transaction>::get(&mut *request)));
+}
+
+#[test]
+fn singletons_created_in_child_are_kept_in_parent() {
+    let mut container = Container::<profiles::Default>::new();
+    let database = {
+        let mut request = container.child();
+        Provider::<OrderHandler>::get(&mut *request).database.clone()
+    };
+
+    assert!(Wrc::ptr_eq(&database, &Provider::<Database>::get(&mut container)));
+}
+
+#[test]
+fn several_children_share_singletons() {
+    let container = Container::<profiles::Default>::new();
+    let mut first = container.child();
+    let mut second = container.child();
+
+    let first_handler = Provider::<OrderHandler>::get(&mut *first);
+    let second_handler = Provider::<OrderHandler>::get(&mut *second);
+    assert!(!Wrc::ptr_eq(&first_handler, &second_handler));
+    assert!(Wrc::ptr_eq(&first_handler.database, &second_handler.database));
+}
+
+#[test]
+fn request_components_are_not_resolved_outside_child() {
+    let mut container = Container::<profiles::Default>::new();
+
+    let error = Provider::<OrderHandler>::try_get(&mut container).err().unwrap();
+    assert!(matches!(error, ResolveError::InactiveScope { .. }));
+    assert_eq!(vec!("request_scope::OrderHandler"), error.path());
+}
+
+#[test]
+fn singletons_do_not_keep_request_components() {
+    let container = Container::<profiles::Default>::new();
+    let mut request = container.child();
+
+    let error = Provider::<Reporter>::try_get(&mut *request).err().unwrap();
+    assert!(matches!(error, ResolveError::CaptiveDependency { .. }), "{}", error);
+    assert_eq!(
+        vec!("request_scope::Reporter", "request_scope::Auditor", "request_scope::Transaction"),
+        error.path()
+    );
+    assert!(!request.components().contains_key(&std::any::TypeId::of::<Auditor>()));
+
+    let exporter = Provider::<Exporter>::get(&mut *request);
+    exporter.transaction.create();
+}
@@ -0,0 +1,125 @@
+#![allow(dead_code)]
+
+use waiter_di::*;
+
+#[component]
+struct Repository {
+    #[prop("missing_db_url")] db_url: String,
+}
+
+#[component]
+struct Service {
+    repository: Wrc<Repository>,
+}
+
+#[component]
+struct BadProp {
+    #[prop("str_prop")] value: i64,
+}
+
+#[test]
+fn missing_property_reports_dependency_path() {
+    let mut container = Container::<profiles::Default>::new();
+
+    let error = Provider::<Service>::try_get(&mut container).err().unwrap();
+
+    assert!(matches!(&error, ResolveError::MissingProperty { property, .. } if property == "missing_db_url"));
+    assert_eq!(2, error.path().len());
+    assert!(error.path()[0].ends_with("Service"));
+    assert!(error.path()[1].ends_with("Repository"));
+    assert!(container.components().is_empty());
+}
+
+#[test]
+fn invalid_property_type() {
+    let mut container = Container::<profiles::Default>::new();
+
+    let error = Provider::<BadProp>::try_create(&mut container).err().unwrap();
+
+    assert!(matches!(error, ResolveError::InvalidProperty { .. }));
+}
+
+#[test]
+#[should_panic(expected = "Property \"missing_db_url\" not found")]
+fn get_panics_with_resolve_error() {
+    let mut container = Container::<profiles::Default>::new();
+    Provider::<Service>::get(&mut container);
+}
+
+#[derive(Debug)]
+struct DbError(String);
+
+impl std::fmt::Display for DbError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Can't connect to {}", self.0)
+    }
+}
+
+impl std::error::Error for DbError {}
+
+struct Db {
+    url: String,
+}
+
+#[provides]
+fn open_db(#[prop("str_prop")] url: String) -> Result<Db, DbError> {
+    if url.starts_with("postgres://") {
+        Ok(Db { url })
+    } else {
+        Err(DbError(url))
+    }
+}
+
+struct Connection;
+
+#[component]
+impl Connection {
+    #[provides]
+    fn connect(db: Wrc<Db>) -> Result<Self, DbError> {
+        Err(DbError(db.url.clone()))
+    }
+}
+
+#[test]
+fn factory_error_is_wrapped() {
+    let mut container = Container::<profiles::Default>::new();
+
+    let error = Provider::<Db>::try_get(&mut container).err().unwrap();
+
+    assert!(matches!(error, ResolveError::Factory { .. }));
+    assert_eq!("Can't connect to str", std::error::Error::source(&error).unwrap().to_string());
+    assert!(error.path()[0].ends_with("Db"));
+}
+
+#[test]
+fn constructor_error_is_wrapped() {
+    let mut container = Container::<profiles::Default>::new();
+
+    let error = Provider::<Connection>::try_create(&mut container).err().unwrap();
+
+    assert_eq!(vec!("Connection", "Db"), error.path().iter()
+        .map(|name| name.rsplit("::").next().unwrap())
+        .collect::<Vec<_>>());
+}
+
+struct Clock {
+    millis: u64,
+}
+
+impl<P> Provider<Clock> for Container<P> {
+    type Impl = Clock;
+    fn get(&mut self) -> Wrc<Clock> {
+        Wrc::new(Provider::<Clock>::create(self))
+    }
+    fn create(&mut self) -> Clock {
+        Clock { millis: 42 }
+    }
+}
+
+#[test]
+fn hand_written_provider_is_fallible() {
+    let mut container = Container::<profiles::Default>::new();
+
+    assert_eq!(42, Provider::<Clock>::try_get(&mut container).unwrap().millis);
+    assert_eq!(42, Provider::<Clock>::try_create(&mut container).unwrap().millis);
+}
@@ -0,0 +1,197 @@
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use waiter_di::*;
+
+static CREATED: AtomicUsize = AtomicUsize::new(0);
+static SESSIONS_ENDED: AtomicUsize = AtomicUsize::new(0);
+static TENANTS_DESTROYED: AtomicUsize = AtomicUsize::new(0);
+static TOKENS_DROPPED: AtomicUsize = AtomicUsize::new(0);
+static REGISTRIES_DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+#[component(scope = "prototype")]
+struct Clock {
+    #[prop("clock.offset")] offset: Option<i64>,
+}
+
+#[component(scope = "prototype")]
+struct Token;
+
+impl Drop for Token {
+    fn drop(&mut self) {
+        TOKENS_DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[component]
+struct Registry {
+    #[prop("str_prop")] name: String,
+}
+
+impl Drop for Registry {
+    fn drop(&mut self) {
+        REGISTRIES_DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[component]
+struct Scheduler {
+    clock: Wrc<Clock>,
+    other_clock: Wrc<Clock>,
+}
+
+#[component(scope = "thread")]
+struct Session;
+
+impl Session {
+    #[pre_destroy]
+    fn end(&self) {
+        SESSIONS_ENDED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Default)]
+struct TenantScope {
+    components: HashMap<TypeId, RcAny>,
+    created: Vec<(TypeId, PreDestroy)>,
+}
+
+impl TenantScope {
+    fn switch_tenant(&mut self) {
+        for (type_id, pre_destroy) in self.created.drain(..) {
+            if let Some(component) = self.components.remove(&type_id) {
+                pre_destroy(&component);
+            }
+        }
+    }
+}
+
+impl Scope for TenantScope {
+    fn get(&mut self, type_id: TypeId) -> Option<RcAny> {
+        self.components.get(&type_id).cloned()
+    }
+    fn put(&mut self, type_id: TypeId, component: RcAny) {
+        self.components.insert(type_id, component);
+    }
+    fn remove(&mut self, type_id: TypeId) {
+        self.components.remove(&type_id);
+    }
+    fn initialized(&mut self, type_id: TypeId, pre_destroy: PreDestroy) {
+        self.created.push((type_id, pre_destroy));
+    }
+    fn close(&mut self) {
+        self.switch_tenant();
+    }
+}
+
+struct TenantSettings {
+    id: usize,
+}
+
+#[component(scope = "TenantScope")]
+impl TenantSettings {
+    #[provides]
+    fn new() -> Self {
+        TenantSettings { id: CREATED.fetch_add(1, Ordering::SeqCst) }
+    }
+
+    #[pre_destroy]
+    fn destroy(&self) {
+        TENANTS_DESTROYED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn prototype_is_created_for_every_injection() {
+    let mut container = Container::<profiles::Default>::new();
+    let scheduler = Provider::<Scheduler>::get(&mut container);
+
+    assert!(!Wrc::ptr_eq(&scheduler.clock, &scheduler.other_clock));
+    assert!(!Wrc::ptr_eq(&scheduler.clock, &Provider::<Clock>::get(&mut container)));
+    assert_eq!(None, scheduler.clock.offset);
+}
+
+#[test]
+fn only_singletons_are_borrowed() {
+    let mut container = Container::<profiles::Default>::new();
+
+    let error = Provider::<Token>::try_get_ref(&mut container).err().unwrap();
+    assert!(matches!(error, ResolveError::NotSingleton { .. }), "{}", error);
+    assert_eq!(0, TOKENS_DROPPED.load(Ordering::SeqCst));
+
+    let registry = Provider::<Registry>::get_ref(&mut container);
+    assert_eq!(0, REGISTRIES_DROPPED.load(Ordering::SeqCst));
+    assert_eq!("str", registry.name);
+
+    drop(container);
+    assert_eq!(1, REGISTRIES_DROPPED.load(Ordering::SeqCst));
+}
+
+#[test]
+fn thread_scope_is_cached_in_thread() {
+    let mut container = Container::<profiles::Default>::new();
+    let session = Provider::<Session>::get(&mut container);
+
+    assert!(Wrc::ptr_eq(&session, &Provider::<Session>::get(&mut container)));
+    assert!(container.components().is_empty());
+
+    container.close();
+    assert_eq!(1, SESSIONS_ENDED.load(Ordering::SeqCst));
+}
+
+#[test]
+fn custom_scope() {
+    let mut container = Container::<profiles::Default>::new();
+    let first = Provider::<TenantSettings>::get(&mut container);
+    assert!(Wrc::ptr_eq(&first, &Provider::<TenantSettings>::get(&mut container)));
+
+    container.scope_mut::<TenantScope>().switch_tenant();
+    assert_eq!(1, TENANTS_DESTROYED.load(Ordering::SeqCst));
+    let second = Provider::<TenantSettings>::get(&mut container);
+    assert_ne!(first.id, second.id);
+
+    container.close();
+    assert_eq!(2, TENANTS_DESTROYED.load(Ordering::SeqCst));
+}
+
+#[cfg(feature = "async")]
+mod shared {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use waiter_di::*;
+
+    static CONNECTIONS_CLOSED: AtomicUsize = AtomicUsize::new(0);
+
+    struct Connection;
+
+    #[component(scope = "thread")]
+    impl Connection {
+        #[provides]
+        fn new() -> Self {
+            Connection
+        }
+
+        #[pre_destroy]
+        fn disconnect(&self) {
+            CONNECTIONS_CLOSED.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn thread_scope_in_shared_container() {
+        let container = SharedContainer::new(Container::<profiles::Default>::new());
+        let main_connection = container.get::<Connection>();
+        assert!(Wrc::ptr_eq(&main_connection, &container.get::<Connection>()));
+
+        let other = container.clone();
+        let (first, second) = std::thread::spawn(move || (other.get::<Connection>(), other.get::<Connection>()))
+            .join()
+            .unwrap();
+        assert!(Wrc::ptr_eq(&first, &second));
+        assert!(!Wrc::ptr_eq(&main_connection, &first));
+
+        container.with_container(|container| container.close());
+        assert_eq!(2, CONNECTIONS_CLOSED.load(Ordering::SeqCst));
+    }
+}
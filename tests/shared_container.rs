@@ -0,0 +1,103 @@
+#![cfg(feature = "async")]
+
+use std::panic;
+use std::sync::{Arc, Barrier};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use waiter_di::*;
+
+static CREATED: AtomicUsize = AtomicUsize::new(0);
+
+struct Pool;
+
+#[provides]
+fn pool() -> Pool {
+    CREATED.fetch_add(1, Ordering::SeqCst);
+    thread::sleep(Duration::from_millis(50));
+    Pool
+}
+
+#[component(scope = "prototype")]
+struct Connection;
+
+static HANDSHAKE: Barrier = Barrier::new(2);
+
+#[component(scope = "prototype")]
+struct Session;
+
+impl Session {
+    // Waits for the session created by another thread, it never returns if sessions are
+    // created one at a time
+    #[post_construct]
+    fn handshake(&self) {
+        HANDSHAKE.wait();
+    }
+}
+
+static FAILED: AtomicBool = AtomicBool::new(false);
+
+struct Cache;
+
+#[provides]
+fn cache() -> Cache {
+    if !FAILED.swap(true, Ordering::SeqCst) {
+        panic!("cache is unavailable");
+    }
+    Cache
+}
+
+#[test]
+fn singleton_is_created_once_by_racing_threads() {
+    let container = SharedContainer::new(Container::<profiles::Default>::new());
+
+    let pools: Vec<Wrc<Pool>> = (0..8)
+        .map(|_| {
+            let container = container.clone();
+            thread::spawn(move || container.get::<Pool>())
+        })
+        .collect::<Vec<_>>()
+        .into_iter()
+        .map(|handle| handle.join().unwrap())
+        .collect();
+
+    assert_eq!(1, CREATED.load(Ordering::SeqCst));
+    assert!(pools.iter().all(|pool| Wrc::ptr_eq(pool, &pools[0])));
+}
+
+#[test]
+fn prototypes_are_not_cached() {
+    let container = SharedContainer::new(Container::<profiles::Default>::new());
+
+    assert!(!Wrc::ptr_eq(&container.get::<Connection>(), &container.get::<Connection>()));
+}
+
+#[test]
+fn prototypes_are_created_in_parallel() {
+    let container = SharedContainer::new(Container::<profiles::Default>::new());
+
+    let handles: Vec<_> = (0..2)
+        .map(|_| {
+            let container = container.clone();
+            thread::spawn(move || container.create::<Session>())
+        })
+        .collect();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+}
+
+#[test]
+fn panicked_resolution_does_not_break_container() {
+    let container = Arc::new(SharedContainer::new(Container::<profiles::Default>::new()));
+
+    let resolving = container.clone();
+    assert!(panic::catch_unwind(move || resolving.get::<Cache>()).is_err());
+
+    let resolving = container.clone();
+    assert!(thread::spawn(move || resolving.try_get::<Cache>()).join().unwrap().is_ok());
+    assert!(container.try_get::<Cache>().is_ok());
+    container.with_container(|container| assert!(!container.is_closed()));
+}
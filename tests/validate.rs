@@ -0,0 +1,108 @@
+#![allow(dead_code)]
+
+use waiter_di::*;
+
+trait Mailer: ContainerSafe {}
+
+trait Storage: ContainerSafe {}
+
+#[component]
+struct LocalStorage;
+
+#[provides(profile = "local")]
+impl Storage for LocalStorage {}
+
+#[component]
+struct CloudStorage {
+    #[prop("cloud.bucket")] bucket: String,
+}
+
+#[provides(profile = "cloud")]
+impl Storage for CloudStorage {}
+
+#[component]
+struct Repository {
+    #[prop("missing_db_url")] db_url: String,
+    #[prop("str_prop")] pool_size: i64,
+}
+
+#[component]
+struct Service {
+    repository: Wrc<Repository>,
+    mailer: Wrc<dyn Mailer>,
+    storage: Box<dyn Storage>,
+}
+
+#[module]
+struct BrokenModule {
+    service: Wrc<Service>,
+}
+
+#[component]
+struct Settings {
+    #[prop("int_v")] retries: i64,
+    #[prop("str_prop")] name: String,
+}
+
+#[module]
+struct ValidModule {
+    settings: Wrc<Settings>,
+    storage: Wrc<dyn Storage>,
+}
+
+#[test]
+fn all_problems_are_reported_at_once() {
+    let container = Container::<profiles::Default>::with_profiles(&["local", "cloud"]);
+
+    let error = container.validate::<BrokenModule>().err().unwrap();
+
+    let problems: Vec<String> = error.problems.iter().map(|problem| problem.to_string()).collect();
+    assert_eq!(4, problems.len(), "{}", error);
+    assert!(matches!(
+        &error.problems[0],
+        ResolveError::MissingProperty { property, path } if property == "missing_db_url" && path.len() == 3
+    ));
+    assert!(matches!(&error.problems[1], ResolveError::InvalidProperty { property, .. } if property == "str_prop"));
+    assert!(matches!(&error.problems[2], ResolveError::NoProvider { .. }));
+    assert!(matches!(&error.problems[3], ResolveError::AmbiguousProvider { candidates, .. } if candidates.len() == 2));
+    assert!(error.to_string().starts_with("Found 4 problem(s):"));
+}
+
+#[test]
+fn valid_module() {
+    let container = Container::<profiles::Default>::with_profiles(&["local"]);
+
+    assert!(container.validate::<ValidModule>().is_ok());
+}
+
+#[test]
+fn implementation_dependencies_are_validated() {
+    let container = Container::<profiles::Default>::with_profiles(&["cloud"]);
+
+    let error = container.validate::<ValidModule>().err().unwrap();
+
+    assert_eq!(1, error.problems.len());
+    assert!(matches!(&error.problems[0], ResolveError::MissingProperty { property, .. } if property == "cloud.bucket"));
+}
+
+trait Notifier: ContainerSafe {}
+
+#[component]
+struct ConsoleNotifier;
+
+#[provides(profiles::Dev)]
+impl Notifier for ConsoleNotifier {}
+
+#[module]
+struct NotifierModule {
+    notifier: Wrc<dyn Notifier>,
+}
+
+#[test]
+fn implementations_of_other_profiles_are_reported() {
+    let error = Container::<profiles::Default>::new().validate::<NotifierModule>().err().unwrap();
+
+    assert_eq!(1, error.problems.len(), "{}", error);
+    assert!(matches!(&error.problems[0], ResolveError::NoProvider { .. }));
+    assert!(Container::<profiles::Dev>::new().validate::<NotifierModule>().is_ok());
+}
@@ -0,0 +1,54 @@
+use std::cell::Cell;
+
+use waiter_di::*;
+
+thread_local! {
+    static DROPPED: Cell<usize> = const { Cell::new(0) };
+}
+
+trait Parent: ContainerSafe {
+    fn name(&self) -> &'static str;
+}
+
+#[component]
+struct Root {
+    child: Wrc<Child>,
+}
+
+#[provides]
+impl Parent for Root {
+    fn name(&self) -> &'static str {
+        "root"
+    }
+}
+
+impl Drop for Root {
+    fn drop(&mut self) {
+        DROPPED.with(|dropped| dropped.set(dropped.get() + 1));
+    }
+}
+
+#[component]
+struct Child {
+    parent: WeakRef<dyn Parent>,
+    root: WeakRef<Root>,
+}
+
+impl Drop for Child {
+    fn drop(&mut self) {
+        DROPPED.with(|dropped| dropped.set(dropped.get() + 1));
+    }
+}
+
+#[test]
+fn cycle_is_dropped_with_container() {
+    let mut container = Container::<profiles::Default>::new();
+    let child = Provider::<Root>::get(&mut container).child.clone();
+    assert_eq!("root", child.parent.get().name());
+    assert!(child.root.upgrade().is_some());
+    drop(child);
+
+    assert!(container.close().is_empty());
+    drop(container);
+    assert_eq!(2, DROPPED.with(Cell::get));
+}